@@ -0,0 +1,46 @@
+//! Benchmarks every completed day on its real puzzle input, timing parsing and each part separately.
+//!
+//! Days without a `src/dayNN/input.txt` are skipped. Save a baseline before changing a solution and compare against
+//! it afterwards:
+//!
+//! ```console
+//! $ cargo bench --bench days -- --save-baseline before
+//! $ cargo bench --bench days -- --baseline before
+//! ```
+
+use aoc2023::input;
+use aoc2023::registry::{Part, DAYS};
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::hint::black_box;
+use std::path::Path;
+use std::time::Duration;
+
+fn days(c: &mut Criterion) {
+    for day in &DAYS {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(format!("src/day{:02}/input.txt", day.number));
+        let Ok(input) = input::load(&path) else {
+            eprintln!("Skipping day {:02}: no puzzle input at {}", day.number, path.display());
+            continue;
+        };
+        let solution = match day.parse(&input) {
+            Ok(solution) => solution,
+            Err(e) => {
+                eprintln!("Skipping day {:02}: {e}", day.number);
+                continue;
+            }
+        };
+
+        // Some parts take seconds per run, so keep the sample count low enough for the suite to finish
+        let mut group = c.benchmark_group(format!("day{:02}", day.number));
+        group.sample_size(10).measurement_time(Duration::from_secs(5));
+
+        group.bench_function("parse", |b| b.iter(|| day.parse(black_box(&input))));
+        for part in Part::BOTH {
+            group.bench_function(format!("part_{part}"), |b| b.iter(|| solution.solve(black_box(part))));
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, days);
+criterion_main!(benches);
@@ -0,0 +1,13 @@
+#![no_main]
+
+use aoc2023::day02::GameRecord;
+use aoc2023::Solution;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    // Input that parses must never make a part panic or run forever, only return an error
+    if let Ok(solution) = GameRecord::parse(input) {
+        let _ = solution.part_a();
+        let _ = solution.part_b();
+    }
+});
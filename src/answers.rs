@@ -0,0 +1,43 @@
+use crate::registry::Part;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// The known-correct answers of one input file
+#[derive(Deserialize, Default, Debug)]
+struct InputAnswers {
+    a: Option<String>,
+    b: Option<String>,
+}
+
+/// Known-correct answers, keyed by day, input name and part
+///
+/// The answers are stored as TOML, with one table per input of each day:
+///
+/// ```toml
+/// [day05.example]
+/// a = "35"
+/// b = "46"
+/// ```
+///
+/// Answers are strings so that they are compared exactly as the solutions print them.
+#[derive(Deserialize, Default, Debug)]
+pub struct Answers(BTreeMap<String, BTreeMap<String, InputAnswers>>);
+
+impl Answers {
+    /// Reads the answers from a TOML file
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path).map_err(|e| format!("Could not read {}: {e}", path.display()))?;
+        toml::from_str(&contents).map_err(|e| format!("Could not parse {}: {e}", path.display()))
+    }
+
+    /// Returns the known answer to one part of a day for the named input, if there is one
+    pub fn get(&self, day: u8, input: &str, part: Part) -> Option<&str> {
+        let answers = self.0.get(&format!("day{day:02}"))?.get(input)?;
+        match part {
+            Part::A => answers.a.as_deref(),
+            Part::B => answers.b.as_deref(),
+        }
+    }
+}
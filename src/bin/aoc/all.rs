@@ -0,0 +1,96 @@
+use crate::{input_name, input_path, print_answers, run_day, DayRun, Reading};
+use aoc2023::registry::{Part, DAYS};
+use std::process::ExitCode;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Formats a duration for the timing table, or a dash for a step that didn't run
+fn cell(duration: Option<Duration>) -> String {
+    duration.map_or("-".to_string(), |d| format!("{d:.2?}"))
+}
+
+/// Prints how long each day's parsing and parts took, with totals for each column
+fn print_timings(runs: &[Option<DayRun>], wall: Duration) {
+    println!(
+        "\n{:<5} {:>12} {:>12} {:>12} {:>12}",
+        "Day", "Parse", "Part A", "Part B", "Total"
+    );
+
+    let mut totals = [Duration::ZERO; 4];
+    for (day, run) in DAYS.iter().zip(runs) {
+        let mut row = [None; 4];
+        if let Some(run) = run {
+            row[0] = Some(run.parse);
+            for (part, _, elapsed) in &run.parts {
+                row[if *part == Part::A { 1 } else { 2 }] = Some(*elapsed);
+            }
+            row[3] = Some(row[..3].iter().flatten().sum());
+        }
+        for (total, time) in totals.iter_mut().zip(row) {
+            *total += time.unwrap_or_default();
+        }
+        let [parse, a, b, total] = row.map(cell);
+        println!(
+            "{:<5} {parse:>12} {a:>12} {b:>12} {total:>12}",
+            format!("{:02}", day.number)
+        );
+    }
+
+    let [parse, a, b, total] = totals.map(|t| cell(Some(t)));
+    println!("{:<5} {parse:>12} {a:>12} {b:>12} {total:>12}", "Total");
+    println!(
+        "\nFinished in {wall:.2?} of wall-clock time across {} threads",
+        DAYS.len()
+    );
+}
+
+/// Solves every completed day at once, each on its own thread, printing the answers in calendar order
+pub fn all(example: bool, json: bool, strict: bool) -> ExitCode {
+    let name = input_name(example.then_some(1));
+    let start = Instant::now();
+    let results: Vec<Result<DayRun, String>> = thread::scope(|scope| {
+        let handles: Vec<_> = DAYS
+            .iter()
+            .map(|day| scope.spawn(|| run_day(day, &input_path(day, &name), None, Reading::Text { strict })))
+            .collect();
+        handles
+            .into_iter()
+            .zip(&DAYS)
+            .map(|(handle, day)| {
+                handle
+                    .join()
+                    .unwrap_or_else(|_| Err(format!("Day {:02} panicked.", day.number)))
+            })
+            .collect()
+    });
+    let wall = start.elapsed();
+
+    // Keep going after a failed day so that one bad input doesn't hide the other answers
+    let mut failed = false;
+    let mut runs = Vec::new();
+    for (day, result) in DAYS.iter().zip(results) {
+        if !json {
+            println!("Day {:02}", day.number);
+        }
+        match result {
+            Ok(run) => {
+                failed |= !print_answers(&run, json);
+                runs.push(Some(run));
+            }
+            Err(e) => {
+                eprintln!("{e}");
+                failed = true;
+                runs.push(None);
+            }
+        }
+    }
+
+    if !json {
+        print_timings(&runs, wall);
+    }
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
@@ -0,0 +1,358 @@
+use serde::Deserialize;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const YEAR: u16 = 2023;
+const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+/// The site asks that automated tools say where they come from
+const USER_AGENT: &str = "github.com/linguini1/aoc2023 runner";
+/// The least time between two requests to the site, unless configured otherwise
+const DEFAULT_RATE_LIMIT: Duration = Duration::from_secs(5);
+
+/// The config file, every key of which is optional
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    session: Option<String>,
+    base_url: Option<String>,
+    cache_dir: Option<PathBuf>,
+    rate_limit_secs: Option<f64>,
+}
+
+/// How to reach the Advent of Code site, read from the config file and then overridden by the environment
+pub struct Settings {
+    /// The site's address, which tests point at a local stub server instead
+    pub base_url: String,
+    /// The `session` cookie of a logged-in browser, which is needed to fetch puzzle inputs
+    pub session: Option<String>,
+    /// Where downloads are kept, along with when the last request was made
+    pub cache_dir: PathBuf,
+    /// The least time to leave between two requests to the site
+    pub rate_limit: Duration,
+}
+
+/// Returns a directory under the user's home, following the XDG variable if it is set
+fn user_dir(xdg_var: &str, fallback: &str) -> Option<PathBuf> {
+    env::var_os(xdg_var)
+        .map(PathBuf::from)
+        .or_else(|| Some(PathBuf::from(env::var_os("HOME")?).join(fallback)))
+}
+
+impl Settings {
+    /// The config file's path, which is `$AOC_CONFIG` if set
+    pub fn config_path() -> Option<PathBuf> {
+        env::var_os("AOC_CONFIG")
+            .map(PathBuf::from)
+            .or_else(|| Some(user_dir("XDG_CONFIG_HOME", ".config")?.join("aoc2023/config.toml")))
+    }
+
+    /// Reads the settings from the config file, if there is one, and the `AOC_SESSION`, `AOC_BASE_URL` and
+    /// `AOC_CACHE_DIR` environment variables
+    pub fn load() -> Result<Self, String> {
+        let file = match Self::config_path() {
+            Some(path) if path.exists() => {
+                let text = fs::read_to_string(&path).map_err(|e| format!("Could not read {}: {e}", path.display()))?;
+                toml::from_str(&text).map_err(|e| format!("Malformed config in {}: {e}", path.display()))?
+            }
+            _ => ConfigFile::default(),
+        };
+
+        let rate_limit = match file.rate_limit_secs {
+            Some(secs) => Duration::try_from_secs_f64(secs).map_err(|e| format!("Invalid `rate_limit_secs`: {e}"))?,
+            None => DEFAULT_RATE_LIMIT,
+        };
+        let cache_dir = env::var_os("AOC_CACHE_DIR")
+            .map(PathBuf::from)
+            .or(file.cache_dir)
+            .or_else(|| Some(user_dir("XDG_CACHE_HOME", ".cache")?.join("aoc2023")))
+            .unwrap_or_else(|| env::temp_dir().join("aoc2023"));
+
+        Ok(Settings {
+            base_url: env::var("AOC_BASE_URL")
+                .ok()
+                .or(file.base_url)
+                .unwrap_or_else(|| DEFAULT_BASE_URL.to_string()),
+            session: env::var("AOC_SESSION").ok().or(file.session),
+            cache_dir,
+            rate_limit,
+        })
+    }
+}
+
+/// A polite client for the Advent of Code site, which caches what it downloads and spaces out its requests
+pub struct Client {
+    settings: Settings,
+    agent: ureq::Agent,
+}
+
+impl Client {
+    pub fn new(settings: Settings) -> Self {
+        let config = ureq::Agent::config_builder()
+            .http_status_as_error(false)
+            .user_agent(USER_AGENT)
+            .timeout_global(Some(Duration::from_secs(30)))
+            .build();
+        Client {
+            settings,
+            agent: config.into(),
+        }
+    }
+
+    fn session(&self) -> Result<&str, String> {
+        self.settings.session.as_deref().ok_or_else(|| {
+            let config = Self::config_hint();
+            format!("No session token: set AOC_SESSION or `session` in {config} to your browser's session cookie.")
+        })
+    }
+
+    fn config_hint() -> String {
+        Settings::config_path().map_or("the config file".to_string(), |p| p.display().to_string())
+    }
+
+    /// Waits until enough time has passed since the last request, including requests from earlier runs
+    fn throttle(&self) -> Result<(), String> {
+        let stamp = self.settings.cache_dir.join("last-request");
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        let last = fs::read_to_string(&stamp)
+            .ok()
+            .and_then(|s| s.trim().parse().ok())
+            .map(Duration::from_millis);
+        if let Some(wait) = last.and_then(|last| (last + self.settings.rate_limit).checked_sub(now)) {
+            thread::sleep(wait);
+        }
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        write(&stamp, &now.as_millis().to_string())
+    }
+
+    fn url(&self, day: u8, page: &str) -> String {
+        format!(
+            "{}/{YEAR}/day/{day}/{page}",
+            self.settings.base_url.trim_end_matches('/')
+        )
+    }
+
+    /// Makes a request to the site as the logged-in user, returning the response's status and body
+    fn send(
+        &self,
+        url: &str,
+        request: impl FnOnce(&ureq::Agent, String) -> Result<ureq::http::Response<ureq::Body>, ureq::Error>,
+    ) -> Result<(u16, String), String> {
+        let cookie = format!("session={}", self.session()?);
+        self.throttle()?;
+        let mut response = request(&self.agent, cookie).map_err(|e| format!("Could not reach {url}: {e}"))?;
+
+        let status = response.status().as_u16();
+        let body = response
+            .body_mut()
+            .read_to_string()
+            .map_err(|e| format!("Could not read the response from {url}: {e}"))?;
+        if status == 400 || status == 500 {
+            let config = Self::config_hint();
+            return Err(format!(
+                "The site rejected the session token; copy a fresh one into AOC_SESSION or {config}."
+            ));
+        }
+        Ok((status, body))
+    }
+
+    /// Returns the day's puzzle input, downloading it only if it isn't already cached
+    pub fn input(&self, day: u8) -> Result<String, String> {
+        let cached = self.settings.cache_dir.join(format!("{YEAR}/day{day:02}.txt"));
+        if let Ok(input) = fs::read_to_string(&cached) {
+            return Ok(input);
+        }
+
+        let url = self.url(day, "input");
+        let (status, body) = self.send(&url, |agent, cookie| agent.get(&url).header("Cookie", cookie).call())?;
+        match status {
+            200 => {}
+            404 => return Err(format!("Day {day}'s puzzle has not unlocked yet.")),
+            _ => return Err(format!("Could not fetch {url}: HTTP {status}: {}", body.trim())),
+        }
+
+        write(&cached, &body)?;
+        Ok(body)
+    }
+
+    /// Posts an answer to one part of a day's puzzle, returning the page the site responds with
+    pub fn submit(&self, day: u8, level: u8, answer: &str) -> Result<String, String> {
+        let url = self.url(day, "answer");
+        let level = level.to_string();
+        let (status, body) = self.send(&url, |agent, cookie| {
+            agent
+                .post(&url)
+                .header("Cookie", cookie)
+                .send_form([("level", level.as_str()), ("answer", answer)])
+        })?;
+        if status != 200 {
+            return Err(format!("Could not submit to {url}: HTTP {status}"));
+        }
+        Ok(body)
+    }
+
+    /// Where the record of submitted answers is kept
+    pub fn history_path(&self) -> PathBuf {
+        self.settings.cache_dir.join(format!("{YEAR}/guesses.jsonl"))
+    }
+}
+
+/// Writes a file, creating its directory first if need be
+pub fn write(path: &Path, contents: &str) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Could not create {}: {e}", dir.display()))?;
+    }
+    fs::write(path, contents).map_err(|e| format!("Could not write {}: {e}", path.display()))
+}
+
+#[cfg(test)]
+pub mod stub {
+    //! A stand-in for the Advent of Code site, serving canned responses on a local port
+
+    use super::Settings;
+    use std::env;
+    use std::fs;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::path::PathBuf;
+    use std::process;
+    use std::sync::mpsc::{self, Receiver};
+    use std::thread;
+    use std::time::Duration;
+
+    /// A fresh cache directory for one test
+    fn cache_dir(test: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("aoc2023-{}-{test}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    /// Settings for talking to the stub server, with no rate limit and a cache of the test's own
+    pub fn settings(base_url: String, test: &str) -> Settings {
+        Settings {
+            base_url,
+            session: Some("abc123".to_string()),
+            cache_dir: cache_dir(test),
+            rate_limit: Duration::ZERO,
+        }
+    }
+
+    /// A request the stub server received
+    pub struct Request {
+        pub method: String,
+        pub path: String,
+        pub cookie: Option<String>,
+        pub body: String,
+    }
+
+    /// Serves each response in turn to one request, returning the server's base URL and the requests it receives
+    pub fn serve(responses: Vec<(u16, &'static str)>) -> (String, Receiver<Request>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let (sender, requests) = mpsc::channel();
+
+        thread::spawn(move || {
+            for (status, body) in responses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut parts = request_line.split_whitespace();
+                let (method, path) = (parts.next().unwrap().to_string(), parts.next().unwrap().to_string());
+
+                let (mut cookie, mut length) = (None, 0);
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    let Some((name, value)) = header.trim_end().split_once(": ") else {
+                        break;
+                    };
+                    match name.to_ascii_lowercase().as_str() {
+                        "cookie" => cookie = Some(value.to_string()),
+                        "content-length" => length = value.parse().unwrap(),
+                        _ => {}
+                    }
+                }
+                let mut request_body = vec![0; length];
+                reader.read_exact(&mut request_body).unwrap();
+
+                let mut stream = reader.into_inner();
+                write!(
+                    stream,
+                    "HTTP/1.1 {status} Stub\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                )
+                .unwrap();
+                let body = String::from_utf8(request_body).unwrap();
+                sender
+                    .send(Request {
+                        method,
+                        path,
+                        cookie,
+                        body,
+                    })
+                    .unwrap();
+            }
+        });
+        (url, requests)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::stub::settings;
+    use super::*;
+    use std::time::Instant;
+
+    #[test]
+    fn downloads_then_reads_from_cache() {
+        let (url, requests) = stub::serve(vec![(200, "0 3 6 9 12 15\n")]);
+        let client = Client::new(settings(url, "cache"));
+
+        assert_eq!(client.input(9).unwrap(), "0 3 6 9 12 15\n");
+        let request = requests.recv().unwrap();
+        assert_eq!(
+            (request.method.as_str(), request.path.as_str()),
+            ("GET", "/2023/day/9/input")
+        );
+        assert_eq!(request.cookie.as_deref(), Some("session=abc123"));
+
+        // The stub only answers once, so a second download would fail
+        assert_eq!(client.input(9).unwrap(), "0 3 6 9 12 15\n");
+    }
+
+    #[test]
+    fn reports_locked_days_and_bad_sessions() {
+        let (url, _requests) = stub::serve(vec![(404, "Not found"), (500, "Internal error")]);
+        let client = Client::new(settings(url, "errors"));
+        assert!(client.input(25).unwrap_err().contains("not unlocked"));
+        assert!(client.input(24).unwrap_err().contains("session token"));
+    }
+
+    #[test]
+    fn needs_a_session_to_download() {
+        let mut settings = settings("http://127.0.0.1:9".to_string(), "session");
+        settings.session = None;
+        assert!(Client::new(settings)
+            .input(1)
+            .unwrap_err()
+            .starts_with("No session token"));
+    }
+
+    #[test]
+    fn spaces_out_requests() {
+        let (url, _requests) = stub::serve(vec![(200, "a\n"), (200, "b\n")]);
+        let mut settings = settings(url, "rate");
+        settings.rate_limit = Duration::from_millis(300);
+        let client = Client::new(settings);
+
+        let start = Instant::now();
+        client.input(1).unwrap();
+        client.input(2).unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(300));
+    }
+}
@@ -0,0 +1,414 @@
+mod all;
+mod client;
+mod profile;
+mod scaffold;
+mod submit;
+mod verify;
+mod watch;
+
+use aoc2023::input::{self, STDIN};
+use aoc2023::registry::{self, Day, Part};
+use aoc2023::SolveError;
+use clap::{Args, Parser, Subcommand};
+use client::{Client, Settings};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+
+/// Runs the Advent of Code 2023 solutions
+#[derive(Parser)]
+#[command(name = "aoc")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Solves a single day
+    Run {
+        /// The day to solve
+        day: u8,
+
+        /// Only solve one part of the puzzle (a or b)
+        #[arg(short, long)]
+        part: Option<Part>,
+
+        #[command(flatten)]
+        input: InputArgs,
+
+        /// Print each part's answer and timing as a JSON object on its own line
+        #[arg(long)]
+        json: bool,
+
+        /// Refuse to solve input which parses but looks like a mistake, such as a repeated number, and report where
+        #[arg(long)]
+        strict: bool,
+
+        /// Read the input as the JSON printed by `dump`, rather than as puzzle text
+        #[arg(long, requires = "input", conflicts_with_all = ["example", "strict"])]
+        parsed: bool,
+    },
+    /// Prints a day's parsed input as JSON, for other tools to inspect or for `run --parsed` to solve from
+    Dump {
+        /// The day whose input to parse
+        day: u8,
+
+        #[command(flatten)]
+        input: InputArgs,
+    },
+    /// Solves every completed day at once, one thread each, then shows how long each took
+    All {
+        /// Use each day's first example input instead of the puzzle input
+        #[arg(long)]
+        example: bool,
+
+        /// Print each part's answer and timing as a JSON object on its own line
+        #[arg(long)]
+        json: bool,
+
+        /// Refuse to solve input which parses but looks like a mistake, such as a repeated number, and report where
+        #[arg(long)]
+        strict: bool,
+    },
+    /// Re-runs a day whenever a file in its src/dayNN directory or its input changes, showing the answers next to
+    /// the previous run's
+    Watch {
+        /// The day to watch
+        day: u8,
+
+        #[command(flatten)]
+        input: InputArgs,
+    },
+    /// Shows the time and heap allocations of each day's parsing and parts, to find what to optimize first
+    Profile {
+        /// The day to profile [default: every completed day]
+        day: Option<u8>,
+
+        /// Use each day's first example input instead of the puzzle input
+        #[arg(long)]
+        example: bool,
+    },
+    /// Checks every day's answers against the known-correct answers
+    Verify {
+        /// The file of known-correct answers
+        #[arg(long, default_value = "answers.toml")]
+        answers: PathBuf,
+    },
+    /// Downloads a day's puzzle input to src/dayNN/input.txt, using the session token in AOC_SESSION or the config
+    /// file
+    Fetch {
+        /// The day to download
+        #[arg(value_parser = clap::value_parser!(u8).range(1..=25))]
+        day: u8,
+    },
+    /// Submits an answer to one part of a day, which is worked out from the puzzle input unless given. Answers ruled
+    /// out by earlier guesses aren't submitted
+    Submit {
+        /// The day to submit an answer for
+        #[arg(value_parser = clap::value_parser!(u8).range(1..=25))]
+        day: u8,
+
+        /// The part to submit an answer for (a or b)
+        #[arg(short, long)]
+        part: Part,
+
+        /// The answer to submit [default: the answer worked out from the day's puzzle input]
+        answer: Option<String>,
+    },
+    /// Sets up a new day: its module from a template, an empty example input, and its place in the runner and
+    /// Makefile. Anything already set up is left as it is
+    New {
+        /// The day to set up
+        #[arg(value_parser = clap::value_parser!(u8).range(1..=25))]
+        day: u8,
+    },
+}
+
+#[derive(Args)]
+struct InputArgs {
+    /// Path to the puzzle input, or `-` for standard input [default: standard input if it is piped, otherwise
+    /// src/dayNN/input.txt]
+    #[arg(short, long, conflicts_with = "example")]
+    input: Option<PathBuf>,
+
+    /// Use the day's example input; days with several examples take the example number
+    #[arg(long, num_args = 0..=1, default_missing_value = "1", value_name = "N")]
+    example: Option<u8>,
+}
+
+/// Returns the name of the puzzle input, or of one of the examples from the puzzle text
+fn input_name(example: Option<u8>) -> String {
+    match example {
+        None => "input".to_string(),
+        Some(1) => "example".to_string(),
+        Some(n) => format!("example{n}"),
+    }
+}
+
+/// Returns the directory holding a day's source and input files
+fn day_dir(day: &Day) -> PathBuf {
+    PathBuf::from(format!("src/day{:02}", day.number))
+}
+
+/// Returns the path of one of a day's input files
+fn input_path(day: &Day, name: &str) -> PathBuf {
+    day_dir(day).join(format!("{name}.txt"))
+}
+
+/// Downloads a day's puzzle input to its usual place, unless it is already there
+fn fetch_input(day: u8) -> Result<PathBuf, String> {
+    let path = PathBuf::from(format!("src/day{day:02}/input.txt"));
+    if !path.exists() {
+        let client = Client::new(Settings::load()?);
+        client::write(&path, &client.input(day)?)?;
+        eprintln!("Fetched {}", path.display());
+    }
+    Ok(path)
+}
+
+/// Works out which file to read a day's input from: the one given, standard input if it's piped, the example asked
+/// for, or else the puzzle input, which is downloaded if need be
+fn resolve_input(day: &Day, input: InputArgs) -> Result<PathBuf, String> {
+    match (input.input, input.example) {
+        (Some(path), _) => Ok(path),
+        (None, None) if stdin_is_piped() => Ok(PathBuf::from(STDIN)),
+        (None, None) => fetch_input(day.number),
+        (None, example) => Ok(input_path(day, &input_name(example))),
+    }
+}
+
+/// Works out the answer to one part of a day from its puzzle input, downloading the input if need be
+fn compute_answer(day: u8, part: Part) -> Result<String, String> {
+    let day = registry::day(day).ok_or(format!(
+        "Day {day} has not been completed, so give the answer to submit."
+    ))?;
+    let path = fetch_input(day.number)?;
+    let contents = input::load(&path).map_err(|e| format!("Could not read {}: {e}", path.display()))?;
+    let solution = day
+        .parse(&contents)
+        .map_err(|e| format!("Malformed input in {}: {e}", path.display()))?;
+    solution
+        .solve(part)
+        .map_err(|e| format!("Could not solve part {part} of {}: {e}", path.display()))
+}
+
+/// Whether standard input is a pipe or a redirected file, rather than a terminal or nothing at all
+fn stdin_is_piped() -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::FileTypeExt;
+        fs::metadata("/dev/stdin").is_ok_and(|m| m.file_type().is_fifo() || m.is_file())
+    }
+    #[cfg(not(unix))]
+    false
+}
+
+/// The answer to one part of a day, as printed in JSON mode
+#[derive(Serialize, Deserialize)]
+struct PartResult {
+    day: u8,
+    part: Part,
+    /// The answer as printed, so that large answers keep their precision in any JSON reader
+    answer: String,
+    elapsed_ns: u64,
+    input: String,
+}
+
+/// A day solved on one input, with how long each step took
+struct DayRun {
+    day: u8,
+    input: String,
+    parse: Duration,
+    /// The answer to each part that was asked for, or why it has none, with how long solving it took
+    parts: Vec<(Part, Result<String, SolveError>, Duration)>,
+}
+
+/// How to read a day's input
+#[derive(Clone, Copy)]
+enum Reading {
+    /// As puzzle text, refusing input that looks like a mistake if strict
+    Text { strict: bool },
+    /// As the JSON of the parsed input, printed by `dump`
+    Parsed,
+}
+
+/// Solves the requested parts of a day, timing the parsing and each part
+fn run_day(day: &Day, path: &Path, part: Option<Part>, reading: Reading) -> Result<DayRun, String> {
+    let name = input::display_name(path);
+    let (solution, parse) = match reading {
+        Reading::Text { strict } => {
+            let contents = input::load(path).map_err(|e| format!("Could not read {name}: {e}"))?;
+            if day.grid {
+                for warning in input::ragged_lines(&contents) {
+                    eprintln!("warning: {name}: {warning}");
+                }
+            }
+
+            let start = Instant::now();
+            let solution = day
+                .parse(&contents)
+                .map_err(|e| format!("Malformed input in {name}: {e}"))?;
+            let parse = start.elapsed();
+
+            if strict {
+                let anomalies = day.anomalies(&contents);
+                if !anomalies.is_empty() {
+                    let report: Vec<_> = anomalies.iter().map(|a| a.to_string()).collect();
+                    return Err(format!("Suspicious input in {name}:\n{}", report.join("\n")));
+                }
+            }
+            (solution, parse)
+        }
+        Reading::Parsed => {
+            let json = input::read(path).map_err(|e| format!("Could not read {name}: {e}"))?;
+            let start = Instant::now();
+            let solution = day
+                .from_json(&json)
+                .map_err(|e| format!("Malformed parsed input in {name}: {e}"))?;
+            (solution, start.elapsed())
+        }
+    };
+
+    let parts = Part::BOTH
+        .into_iter()
+        .filter(|p| part.is_none_or(|part| part == *p))
+        .map(|p| {
+            let start = Instant::now();
+            let answer = solution.solve(p);
+            (p, answer, start.elapsed())
+        })
+        .collect();
+
+    Ok(DayRun {
+        day: day.number,
+        input: name,
+        parse,
+        parts,
+    })
+}
+
+/// Prints each answer on its own line, or as JSON, returning whether every part had an answer
+///
+/// A part without an answer is reported after the others' answers, rather than stopping them from being printed.
+fn print_answers(run: &DayRun, json: bool) -> bool {
+    let mut solved = true;
+    for (part, answer, elapsed) in &run.parts {
+        let answer = match answer {
+            Ok(answer) => answer,
+            Err(e) => {
+                eprintln!("Could not solve part {part} of {}: {e}", run.input);
+                solved = false;
+                continue;
+            }
+        };
+        if json {
+            let result = PartResult {
+                day: run.day,
+                part: *part,
+                answer: answer.clone(),
+                elapsed_ns: elapsed.as_nanos() as u64,
+                input: run.input.clone(),
+            };
+            println!("{}", serde_json::to_string(&result).unwrap());
+        } else {
+            println!("{answer}");
+        }
+    }
+    solved
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    match cli.command {
+        Command::Run {
+            day,
+            part,
+            input,
+            json,
+            strict,
+            parsed,
+        } => {
+            let Some(day) = registry::day(day) else {
+                eprintln!("Day {day} has not been completed.");
+                return ExitCode::FAILURE;
+            };
+            let reading = if parsed {
+                Reading::Parsed
+            } else {
+                Reading::Text { strict }
+            };
+            match resolve_input(day, input).and_then(|path| run_day(day, &path, part, reading)) {
+                Ok(run) => {
+                    if !print_answers(&run, json) {
+                        return ExitCode::FAILURE;
+                    }
+                }
+                Err(e) => {
+                    eprintln!("{e}");
+                    return ExitCode::FAILURE;
+                }
+            }
+        }
+        Command::Dump { day, input } => {
+            let Some(day) = registry::day(day) else {
+                eprintln!("Day {day} has not been completed.");
+                return ExitCode::FAILURE;
+            };
+            let parsed = resolve_input(day, input).and_then(|path| {
+                let name = input::display_name(&path);
+                let contents = input::load(&path).map_err(|e| format!("Could not read {name}: {e}"))?;
+                day.parse(&contents)
+                    .map_err(|e| format!("Malformed input in {name}: {e}"))
+            });
+            match parsed {
+                Ok(solution) => println!("{}", solution.to_json()),
+                Err(e) => {
+                    eprintln!("{e}");
+                    return ExitCode::FAILURE;
+                }
+            }
+        }
+        Command::All { example, json, strict } => return all::all(example, json, strict),
+        Command::Watch { day, input } => {
+            let Some(day) = registry::day(day) else {
+                eprintln!("Day {day} has not been completed.");
+                return ExitCode::FAILURE;
+            };
+            return watch::watch(day, input);
+        }
+        Command::Profile { day, example } => return profile::profile(day, example),
+        Command::Verify { answers } => return verify::verify(&answers),
+        Command::Fetch { day } => {
+            if let Err(e) = fetch_input(day) {
+                eprintln!("{e}");
+                return ExitCode::FAILURE;
+            }
+        }
+        Command::Submit { day, part, answer } => {
+            // The day only needs to be completed if its answer has to be worked out
+            let result = Settings::load().and_then(|settings| {
+                let answer = match answer {
+                    Some(answer) => answer,
+                    None => compute_answer(day, part)?,
+                };
+                let verdict = submit::submit(&Client::new(settings), day, part, &answer)?;
+                println!("Day {day:02} part {part}: {answer} is {verdict}");
+                Ok(verdict)
+            });
+            match result {
+                Ok(submit::Verdict::Correct) => {}
+                Ok(_) => return ExitCode::FAILURE,
+                Err(e) => {
+                    eprintln!("{e}");
+                    return ExitCode::FAILURE;
+                }
+            }
+        }
+        Command::New { day } => return scaffold::new_day(day),
+    }
+    ExitCode::SUCCESS
+}
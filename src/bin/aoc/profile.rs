@@ -0,0 +1,175 @@
+use crate::{input_name, input_path};
+use aoc2023::input;
+use aoc2023::registry::{self, Day, Part, DAYS};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::ops::Sub;
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+static BYTES: AtomicU64 = AtomicU64::new(0);
+
+/// The system allocator, counting every allocation made through it so that each phase of a day can be profiled
+///
+/// Growing an allocation counts as a new allocation of its new size, since that is usually what it costs.
+struct Counting;
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+fn record(size: usize) {
+    ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    BYTES.fetch_add(size as u64, Ordering::Relaxed);
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        record(layout.size());
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        record(layout.size());
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        record(new_size);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+/// How many heap allocations have been made, and how many bytes they asked for
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct Allocations {
+    count: u64,
+    bytes: u64,
+}
+
+impl Allocations {
+    fn now() -> Self {
+        Allocations {
+            count: ALLOCATIONS.load(Ordering::Relaxed),
+            bytes: BYTES.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl Sub for Allocations {
+    type Output = Self;
+
+    fn sub(self, earlier: Self) -> Self {
+        Allocations {
+            count: self.count - earlier.count,
+            bytes: self.bytes - earlier.bytes,
+        }
+    }
+}
+
+/// The cost of one phase of solving a day
+struct Phase {
+    elapsed: Duration,
+    allocations: Allocations,
+}
+
+/// Runs `f`, measuring its wall time and what it allocated
+///
+/// Anything running on other threads at the same time is counted too, so phases are profiled one at a time.
+fn measure<T>(f: impl FnOnce() -> T) -> (T, Phase) {
+    let before = Allocations::now();
+    let start = Instant::now();
+    let result = f();
+    let elapsed = start.elapsed();
+    let phase = Phase {
+        elapsed,
+        allocations: Allocations::now() - before,
+    };
+    (result, phase)
+}
+
+fn print_phase(day: &Day, name: &str, phase: &Phase) {
+    println!(
+        "{:<4} {name:<7} {:>12} {:>12} {:>14}",
+        format!("{:02}", day.number),
+        format!("{:.2?}", phase.elapsed),
+        phase.allocations.count,
+        phase.allocations.bytes
+    );
+}
+
+/// Profiles one day: its parsing and each of its parts
+fn profile_day(day: &Day, example: bool) -> Result<(), String> {
+    let path = input_path(day, &input_name(example.then_some(1)));
+    let contents = input::load(&path).map_err(|e| format!("Could not read {}: {e}", path.display()))?;
+
+    let (solution, phase) = measure(|| day.parse(&contents));
+    let solution = solution.map_err(|e| format!("Malformed input in {}: {e}", path.display()))?;
+    print_phase(day, "parse", &phase);
+
+    let mut unsolved = Vec::new();
+    for part in Part::BOTH {
+        let (answer, phase) = measure(|| solution.solve(part));
+        print_phase(day, &format!("part {part}"), &phase);
+        if let Err(e) = answer {
+            unsolved.push(format!("Could not solve part {part} of {}: {e}", path.display()));
+        }
+    }
+    if unsolved.is_empty() {
+        Ok(())
+    } else {
+        Err(unsolved.join("\n"))
+    }
+}
+
+/// Profiles one day, or every completed day, reporting the time and heap allocations of each phase
+pub fn profile(day: Option<u8>, example: bool) -> ExitCode {
+    let days: Vec<&Day> = match day {
+        Some(number) => match registry::day(number) {
+            Some(day) => vec![day],
+            None => {
+                eprintln!("Day {number} has not been completed.");
+                return ExitCode::FAILURE;
+            }
+        },
+        None => DAYS.iter().collect(),
+    };
+
+    println!(
+        "{:<4} {:<7} {:>12} {:>12} {:>14}",
+        "Day", "Phase", "Time", "Allocs", "Bytes"
+    );
+    let mut failed = false;
+    for day in days {
+        if let Err(e) = profile_day(day, example) {
+            eprintln!("{e}");
+            failed = true;
+        }
+    }
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_allocations() {
+        let (v, phase) = measure(|| {
+            let mut v: Vec<u64> = Vec::with_capacity(4);
+            v.extend([1, 2, 3, 4, 5]);
+            v
+        });
+        // Tests run in parallel, so other threads' allocations may be counted as well
+        assert!(phase.allocations.count >= 2);
+        assert!(phase.allocations.bytes >= 32 + v.capacity() as u64 * 8);
+    }
+}
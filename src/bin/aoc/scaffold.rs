@@ -0,0 +1,211 @@
+use regex::Regex;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+/// The new day's module, with `{{day}}` standing for the day's number
+const TEMPLATE: &str = include_str!("day.rs.template");
+
+const LIB: &str = "src/lib.rs";
+const REGISTRY: &str = "src/registry.rs";
+const MAKEFILE: &str = "Makefile";
+
+/// Starts the Makefile's line listing the completed days, as two-digit numbers
+const COMPLETED_DAYS: &str = "COMPLETED_DAYS =";
+
+/// Finds where a new day's entry goes among the existing ones, keeping them in calendar order
+///
+/// Each entry is `entry_len` lines long, starting with a line that `day_of` reads the day's number from.
+fn insertion_point(lines: &[&str], day_of: impl Fn(&str) -> Option<u8>, number: u8, entry_len: usize) -> Option<usize> {
+    let entries: Vec<(usize, u8)> = lines
+        .iter()
+        .enumerate()
+        .filter_map(|(i, line)| Some((i, day_of(line)?)))
+        .collect();
+    let &(last, _) = entries.last()?;
+    Some(
+        entries
+            .iter()
+            .find(|(_, day)| *day > number)
+            .map_or(last + entry_len, |(i, _)| *i),
+    )
+}
+
+fn insert_lines(text: &str, at: usize, new: &[String]) -> String {
+    let mut lines: Vec<&str> = text.lines().collect();
+    lines.splice(at..at, new.iter().map(String::as_str));
+    lines.join("\n") + "\n"
+}
+
+/// Declares the day's module in the library, or returns `None` if it already is
+fn add_module(lib: &str, number: u8) -> Result<Option<String>, String> {
+    if lib.lines().any(|l| l == format!("pub mod day{number:02};")) {
+        return Ok(None);
+    }
+    let path = Regex::new(r#"^#\[path = "day(\d+)/day\d+\.rs"\]$"#).unwrap();
+    let lines: Vec<&str> = lib.lines().collect();
+    let day_of = |line: &str| path.captures(line)?[1].parse().ok();
+    let at = insertion_point(&lines, day_of, number, 2).ok_or(format!("found no day modules in {LIB}"))?;
+    let entry = [
+        format!("#[path = \"day{number:02}/day{number:02}.rs\"]"),
+        format!("pub mod day{number:02};"),
+    ];
+    Ok(Some(insert_lines(lib, at, &entry)))
+}
+
+/// Adds the day to the runner's list of days, or returns `None` if it's already there
+fn add_to_registry(registry: &str, number: u8) -> Result<Option<String>, String> {
+    let entry = Regex::new(r"^\s*Day::new::<day(\d+)::\w+>\(\d+\),$").unwrap();
+    let lines: Vec<&str> = registry.lines().collect();
+    let day_of = |line: &str| entry.captures(line)?[1].parse().ok();
+    if lines.iter().any(|l| day_of(l) == Some(number)) {
+        return Ok(None);
+    }
+    let at = insertion_point(&lines, day_of, number, 1).ok_or(format!("found no days in {REGISTRY}"))?;
+    let registry = insert_lines(
+        registry,
+        at,
+        &[format!("    Day::new::<day{number:02}::Puzzle>({number}),")],
+    );
+
+    // The list is a fixed-size array, so its length goes up too
+    let count = registry.lines().filter(|l| entry.is_match(l)).count();
+    let length = Regex::new(r"pub static DAYS: \[Day; \d+\]").unwrap();
+    Ok(Some(
+        length
+            .replace(&registry, format!("pub static DAYS: [Day; {count}]"))
+            .into_owned(),
+    ))
+}
+
+/// Finds the Makefile's line of completed days, returning which line it is and the days on it
+fn completed_days_line(makefile: &str) -> Result<(usize, Vec<&str>), String> {
+    let (i, line) = makefile
+        .lines()
+        .enumerate()
+        .find(|(_, l)| l.starts_with(COMPLETED_DAYS))
+        .ok_or(format!("found no `{COMPLETED_DAYS}` line in {MAKEFILE}"))?;
+    Ok((i, line[COMPLETED_DAYS.len()..].split_whitespace().collect()))
+}
+
+/// Reads the numbers of the days the Makefile lists as completed
+fn parse_completed_days(makefile: &str) -> Result<Vec<u8>, String> {
+    let (_, days) = completed_days_line(makefile)?;
+    days.into_iter()
+        .map(|day| {
+            day.parse()
+                .map_err(|_| format!("`{day}` in {MAKEFILE}'s completed days is not a day number"))
+        })
+        .collect()
+}
+
+/// Returns the days the Makefile lists as completed, which `aoc new` adds to and `verify` checks
+pub fn completed_days() -> Result<Vec<u8>, String> {
+    let makefile = fs::read_to_string(MAKEFILE).map_err(|e| format!("Could not read {MAKEFILE}: {e}"))?;
+    parse_completed_days(&makefile)
+}
+
+/// Adds the day to the Makefile's completed days, or returns `None` if it's already there
+fn add_to_makefile(makefile: &str, number: u8) -> Result<Option<String>, String> {
+    let (i, mut days) = completed_days_line(makefile)?;
+    let day = format!("{number:02}");
+    if days.contains(&day.as_str()) {
+        return Ok(None);
+    }
+    days.push(&day);
+    days.sort();
+
+    let mut lines: Vec<String> = makefile.lines().map(str::to_string).collect();
+    lines[i] = format!("{COMPLETED_DAYS} {}", days.join(" "));
+    Ok(Some(lines.join("\n") + "\n"))
+}
+
+/// Creates a file unless it already exists, reporting which happened
+fn create(path: &Path, contents: &str) -> Result<(), String> {
+    if path.exists() {
+        println!("exists   {}", path.display());
+        return Ok(());
+    }
+    fs::write(path, contents).map_err(|e| format!("Could not write {}: {e}", path.display()))?;
+    println!("created  {}", path.display());
+    Ok(())
+}
+
+/// Applies an edit to a file, reporting whether it changed anything
+fn update(path: &str, edit: impl Fn(&str, u8) -> Result<Option<String>, String>, number: u8) -> Result<(), String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("Could not read {path}: {e}"))?;
+    match edit(&text, number).map_err(|e| format!("Could not update {path}: {e}"))? {
+        Some(text) => {
+            fs::write(path, text).map_err(|e| format!("Could not write {path}: {e}"))?;
+            println!("updated  {path}");
+        }
+        None => println!("current  {path}"),
+    }
+    Ok(())
+}
+
+fn scaffold(number: u8) -> Result<(), String> {
+    if !Path::new(LIB).exists() {
+        return Err(format!("Could not find {LIB}; run this from the repository root."));
+    }
+
+    let dir = PathBuf::from(format!("src/day{number:02}"));
+    fs::create_dir_all(&dir).map_err(|e| format!("Could not create {}: {e}", dir.display()))?;
+    create(
+        &dir.join(format!("day{number:02}.rs")),
+        &TEMPLATE.replace("{{day}}", &number.to_string()),
+    )?;
+    create(&dir.join("example.txt"), "")?;
+
+    update(LIB, add_module, number)?;
+    update(REGISTRY, add_to_registry, number)?;
+    update(MAKEFILE, add_to_makefile, number)
+}
+
+/// Sets up a new day from the template, leaving anything that already exists as it is
+pub fn new_day(number: u8) -> ExitCode {
+    match scaffold(number) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIB_TEXT: &str = "pub mod answers;\n#[path = \"day01/day01.rs\"]\npub mod day01;\n#[path = \"day03/day03.rs\"]\npub mod day03;\npub mod error;\n";
+
+    #[test]
+    fn declares_modules_in_order() {
+        let lib = add_module(LIB_TEXT, 2).unwrap().unwrap();
+        assert!(lib.contains("pub mod day01;\n#[path = \"day02/day02.rs\"]\npub mod day02;\n#[path = \"day03"));
+        let lib = add_module(&lib, 4).unwrap().unwrap();
+        assert!(lib.ends_with("pub mod day03;\n#[path = \"day04/day04.rs\"]\npub mod day04;\npub mod error;\n"));
+        assert_eq!(add_module(&lib, 4), Ok(None));
+    }
+
+    #[test]
+    fn registers_days_in_order() {
+        let registry = "pub static DAYS: [Day; 2] = [\n    Day::new::<day01::Document>(1),\n    Day::new::<day11::Image>(11),\n];\n";
+        let registry = add_to_registry(registry, 10).unwrap().unwrap();
+        assert_eq!(
+            registry,
+            "pub static DAYS: [Day; 3] = [\n    Day::new::<day01::Document>(1),\n    Day::new::<day10::Puzzle>(10),\n    Day::new::<day11::Image>(11),\n];\n"
+        );
+        assert_eq!(add_to_registry(&registry, 10), Ok(None));
+    }
+
+    #[test]
+    fn adds_completed_days_once() {
+        let makefile = "COMPLETED_DAYS = 01 02 11\nINPUT = input.txt\n";
+        let makefile = add_to_makefile(makefile, 3).unwrap().unwrap();
+        assert_eq!(makefile, "COMPLETED_DAYS = 01 02 03 11\nINPUT = input.txt\n");
+        assert_eq!(add_to_makefile(&makefile, 3), Ok(None));
+        assert_eq!(parse_completed_days(&makefile), Ok(vec![1, 2, 3, 11]));
+        assert!(parse_completed_days("COMPLETED_DAYS = 01 two\n").is_err());
+    }
+}
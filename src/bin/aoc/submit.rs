@@ -0,0 +1,256 @@
+use crate::client::{self, Client};
+use aoc2023::registry::Part;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+/// What the site made of a submitted answer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    Correct,
+    TooHigh,
+    TooLow,
+    /// Wrong, without a hint as to which way
+    Wrong,
+    /// Submitted too soon after the last wrong answer, so the answer wasn't checked
+    Wait {
+        seconds: u64,
+    },
+    /// The part had already been solved, so the answer wasn't checked
+    AlreadySolved,
+    /// The site's response couldn't be read, so whether the answer was checked, or right, isn't known
+    Unknown,
+}
+
+impl Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Correct => write!(f, "correct"),
+            Self::TooHigh => write!(f, "too high"),
+            Self::TooLow => write!(f, "too low"),
+            Self::Wrong => write!(f, "wrong"),
+            Self::Wait { seconds } => write!(f, "not checked, wait {seconds}s before trying again"),
+            Self::AlreadySolved => write!(f, "not checked, this part is already solved"),
+            Self::Unknown => write!(f, "unknown, the site's response couldn't be read"),
+        }
+    }
+}
+
+/// Reads the verdict from the page the site responds to an answer with
+fn parse_verdict(page: &str) -> Option<Verdict> {
+    if page.contains("That's the right answer") {
+        Some(Verdict::Correct)
+    } else if page.contains("your answer is too high") {
+        Some(Verdict::TooHigh)
+    } else if page.contains("your answer is too low") {
+        Some(Verdict::TooLow)
+    } else if page.contains("That's not the right answer") {
+        Some(Verdict::Wrong)
+    } else if page.contains("You gave an answer too recently") {
+        let left = Regex::new(r"You have (?:(\d+)m ?)?(?:(\d+)s )?left to wait").unwrap();
+        let captures = left.captures(page)?;
+        let count = |i| captures.get(i).map_or(Some(0), |n| n.as_str().parse::<u64>().ok());
+        Some(Verdict::Wait {
+            seconds: count(1)? * 60 + count(2)?,
+        })
+    } else if page.contains("You don't seem to be solving the right level") {
+        Some(Verdict::AlreadySolved)
+    } else {
+        None
+    }
+}
+
+/// One submitted answer, as kept in the history file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Guess {
+    day: u8,
+    part: Part,
+    answer: String,
+    verdict: Verdict,
+}
+
+/// Every answer submitted so far, kept as one JSON object per line
+struct History {
+    path: PathBuf,
+    guesses: Vec<Guess>,
+}
+
+impl History {
+    fn load(path: PathBuf) -> Result<Self, String> {
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(_) if !path.exists() => String::new(),
+            Err(e) => return Err(format!("Could not read {}: {e}", path.display())),
+        };
+        let guesses = text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| {
+                serde_json::from_str(line).map_err(|e| format!("Malformed guess in {}:{}: {e}", path.display(), i + 1))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(History { path, guesses })
+    }
+
+    /// Returns why the answer shouldn't be submitted, if what's already been learned rules it out
+    fn rule_out(&self, day: u8, part: Part, answer: &str) -> Option<String> {
+        let guesses: Vec<_> = self.guesses.iter().filter(|g| g.day == day && g.part == part).collect();
+        if let Some(correct) = guesses.iter().find(|g| g.verdict == Verdict::Correct) {
+            return Some(format!(
+                "day {day} part {part} was already solved with {}",
+                correct.answer
+            ));
+        }
+        let checked = |g: &&&Guess| {
+            !matches!(
+                g.verdict,
+                Verdict::Wait { .. } | Verdict::AlreadySolved | Verdict::Unknown
+            )
+        };
+        if guesses.iter().filter(checked).any(|g| g.answer == answer) {
+            return Some(format!("{answer} was already guessed, and it was wrong"));
+        }
+
+        let value: i128 = answer.parse().ok()?;
+        let bound = |verdict| {
+            guesses
+                .iter()
+                .filter(move |g| g.verdict == verdict)
+                .filter_map(|g| Some((g.answer.parse::<i128>().ok()?, &g.answer)))
+        };
+        if let Some((_, low)) = bound(Verdict::TooLow).filter(|(low, _)| value <= *low).max() {
+            return Some(format!("{answer} can't be right, since {low} was too low"));
+        }
+        if let Some((_, high)) = bound(Verdict::TooHigh).filter(|(high, _)| value >= *high).min() {
+            return Some(format!("{answer} can't be right, since {high} was too high"));
+        }
+        None
+    }
+
+    fn record(&mut self, guess: Guess) -> Result<(), String> {
+        let line = serde_json::to_string(&guess).unwrap() + "\n";
+        if !self.path.exists() {
+            client::write(&self.path, "")?;
+        }
+        OpenOptions::new()
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .map_err(|e| format!("Could not write {}: {e}", self.path.display()))?;
+        self.guesses.push(guess);
+        Ok(())
+    }
+}
+
+/// Submits an answer to one part of a day, unless earlier guesses already rule it out
+pub fn submit(client: &Client, day: u8, part: Part, answer: &str) -> Result<Verdict, String> {
+    let mut history = History::load(client.history_path())?;
+    if let Some(reason) = history.rule_out(day, part, answer) {
+        return Err(format!("Not submitting: {reason}."));
+    }
+
+    let level = match part {
+        Part::A => 1,
+        Part::B => 2,
+    };
+    let page = client.submit(day, level, answer)?;
+
+    // The answer has been submitted whether or not the response makes sense, so it's recorded either way
+    let verdict = parse_verdict(&page);
+    history.record(Guess {
+        day,
+        part,
+        answer: answer.to_string(),
+        verdict: verdict.clone().unwrap_or(Verdict::Unknown),
+    })?;
+    verdict.ok_or_else(|| {
+        "Could not make sense of the site's response to the answer, so it's recorded with an unknown verdict."
+            .to_string()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::stub;
+
+    /// The site's response pages, cut down to the part that matters
+    const CORRECT: &str = "<main><article><p>That's the right answer!  You are one gold star closer to restoring \
+                           snow operations. <a href=\"/2023/day/5#part2\">[Continue to Part Two]</a></p></article></main>";
+    const TOO_HIGH: &str = "<main><article><p>That's not the right answer; your answer is too high.  If you're stuck, \
+                            make sure you're using the full input data; there are also some general tips on the \
+                            <a href=\"/2023/about\">about page</a>.  Please wait one minute before trying again. \
+                            <a href=\"/2023/day/5\">[Return to Day 5]</a></p></article></main>";
+    const TOO_LOW: &str = "<main><article><p>That's not the right answer; your answer is too low.  Please wait one \
+                           minute before trying again.</p></article></main>";
+    const WAIT: &str = "<main><article><p>You gave an answer too recently; you have to wait after submitting an \
+                        answer before trying again.  You have 1m 5s left to wait. <a href=\"/2023/day/5\">[Return \
+                        to Day 5]</a></p></article></main>";
+
+    #[test]
+    fn reads_verdicts() {
+        assert_eq!(parse_verdict(CORRECT), Some(Verdict::Correct));
+        assert_eq!(parse_verdict(TOO_HIGH), Some(Verdict::TooHigh));
+        assert_eq!(parse_verdict(TOO_LOW), Some(Verdict::TooLow));
+        assert_eq!(parse_verdict(WAIT), Some(Verdict::Wait { seconds: 65 }));
+        let short_wait = WAIT.replace("1m 5s", "37s");
+        assert_eq!(parse_verdict(&short_wait), Some(Verdict::Wait { seconds: 37 }));
+        assert_eq!(parse_verdict("<html>Something else</html>"), None);
+    }
+
+    #[test]
+    fn submits_and_learns_bounds() {
+        let (url, requests) = stub::serve(vec![(200, TOO_HIGH), (200, TOO_LOW), (200, CORRECT)]);
+        let client = Client::new(stub::settings(url, "submit"));
+
+        assert_eq!(submit(&client, 5, Part::B, "100"), Ok(Verdict::TooHigh));
+        let request = requests.recv().unwrap();
+        assert_eq!(
+            (request.method.as_str(), request.path.as_str()),
+            ("POST", "/2023/day/5/answer")
+        );
+        assert_eq!(request.body, "level=2&answer=100");
+
+        assert_eq!(submit(&client, 5, Part::B, "10"), Ok(Verdict::TooLow));
+        for ruled_out in ["100", "150", "10", "7"] {
+            assert!(submit(&client, 5, Part::B, ruled_out)
+                .unwrap_err()
+                .starts_with("Not submitting"));
+        }
+        // Bounds are kept per part
+        assert_eq!(submit(&client, 5, Part::A, "150"), Ok(Verdict::Correct));
+        assert!(submit(&client, 5, Part::A, "46")
+            .unwrap_err()
+            .contains("already solved with 150"));
+
+        let history = History::load(client.history_path()).unwrap();
+        let verdicts: Vec<_> = history.guesses.iter().map(|g| g.verdict.clone()).collect();
+        assert_eq!(verdicts, [Verdict::TooHigh, Verdict::TooLow, Verdict::Correct]);
+    }
+
+    #[test]
+    fn records_unreadable_responses() {
+        let (url, _requests) = stub::serve(vec![(200, "<html>Something else</html>"), (200, CORRECT)]);
+        let client = Client::new(stub::settings(url, "unknown"));
+        assert!(submit(&client, 7, Part::B, "5905")
+            .unwrap_err()
+            .contains("unknown verdict"));
+
+        let history = History::load(client.history_path()).unwrap();
+        assert_eq!(history.guesses[0].verdict, Verdict::Unknown);
+        // It may not have been checked, so it can be tried again
+        assert_eq!(submit(&client, 7, Part::B, "5905"), Ok(Verdict::Correct));
+    }
+
+    #[test]
+    fn waiting_is_not_a_wrong_answer() {
+        let (url, _requests) = stub::serve(vec![(200, WAIT), (200, CORRECT)]);
+        let client = Client::new(stub::settings(url, "wait"));
+        assert_eq!(submit(&client, 6, Part::A, "288"), Ok(Verdict::Wait { seconds: 65 }));
+        assert_eq!(submit(&client, 6, Part::A, "288"), Ok(Verdict::Correct));
+    }
+}
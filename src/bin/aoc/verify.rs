@@ -0,0 +1,108 @@
+use crate::{day_dir, input_path, scaffold};
+use aoc2023::answers::Answers;
+use aoc2023::input;
+use aoc2023::registry::{self, Day, Part};
+use std::fs;
+use std::path::Path;
+use std::process::ExitCode;
+
+/// The outcome of checking one part of a day against its known answer
+enum Status {
+    Pass,
+    Fail(String),
+    Missing,
+}
+
+/// Returns the names of every input file kept alongside a day's source, such as `input` and `example`
+fn input_names(day: &Day) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(day_dir(day)) else {
+        return vec![];
+    };
+
+    let mut names: Vec<_> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "txt"))
+        .filter_map(|p| Some(p.file_stem()?.to_str()?.to_string()))
+        .collect();
+    names.sort();
+    names
+}
+
+/// Checks both parts of a day's input against the known answers
+fn check(day: &Day, name: &str, answers: &Answers) -> [Status; 2] {
+    let expected = Part::BOTH.map(|p| answers.get(day.number, name, p));
+
+    // Parts without a known answer aren't run at all: some examples only apply to one part, and the other
+    // part may not make sense for them
+    if expected.iter().all(|e| e.is_none()) {
+        return [Status::Missing, Status::Missing];
+    }
+
+    let path = input_path(day, name);
+    let solution = input::load(&path)
+        .map_err(|e| format!("could not read {}: {e}", path.display()))
+        .and_then(|contents| day.parse(&contents).map_err(|e| e.to_string()));
+
+    Part::BOTH.map(|part| {
+        let Some(expected) = answers.get(day.number, name, part) else {
+            return Status::Missing;
+        };
+        match &solution {
+            Err(e) => Status::Fail(e.clone()),
+            Ok(solution) => match solution.solve(part) {
+                Ok(answer) if answer == expected => Status::Pass,
+                Ok(answer) => Status::Fail(format!("expected {expected}, got {answer}")),
+                Err(e) => Status::Fail(e.to_string()),
+            },
+        }
+    })
+}
+
+/// Runs every day the Makefile lists as completed on each of its inputs, reporting which answers match the known
+/// answers
+pub fn verify(answers_path: &Path) -> ExitCode {
+    let (answers, days) = match Answers::load(answers_path).and_then(|a| Ok((a, scaffold::completed_days()?))) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let (mut passed, mut failed, mut missing) = (0, 0, 0);
+    for number in days {
+        // A day listed in the Makefile but missing from the runner can't be checked at all
+        let Some(day) = registry::day(number) else {
+            failed += 1;
+            println!("day{number:02}  FAIL  listed as completed in the Makefile, but not in the runner");
+            continue;
+        };
+        for name in input_names(day) {
+            for (part, status) in Part::BOTH.into_iter().zip(check(day, &name, &answers)) {
+                let label = format!("day{:02} {name:<9} {part}", day.number);
+                match status {
+                    Status::Pass => {
+                        passed += 1;
+                        println!("{label}  pass");
+                    }
+                    Status::Fail(reason) => {
+                        failed += 1;
+                        println!("{label}  FAIL  {reason}");
+                    }
+                    Status::Missing => {
+                        missing += 1;
+                        println!("{label}  missing");
+                    }
+                }
+            }
+        }
+    }
+
+    println!("\n{passed} passed, {failed} failed, {missing} missing");
+    if failed > 0 {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
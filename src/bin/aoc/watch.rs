@@ -0,0 +1,146 @@
+use crate::{day_dir, InputArgs, PartResult};
+use aoc2023::input::STDIN;
+use aoc2023::registry::Day;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitCode, Stdio};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// How often to look for changes
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// When each watched file was last modified
+type Snapshot = BTreeMap<PathBuf, SystemTime>;
+
+/// Notes when every file in the watched directories, and every other watched file, was last modified
+fn snapshot(dirs: &[PathBuf], files: &[PathBuf]) -> Snapshot {
+    let in_dirs = dirs
+        .iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flatten()
+        .filter_map(|entry| Some(entry.ok()?.path()));
+    in_dirs
+        .chain(files.iter().cloned())
+        .filter_map(|path| {
+            let modified = fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+            Some((path, modified))
+        })
+        .collect()
+}
+
+/// Rebuilds the runner and solves the day with it, returning each part's result, or nothing if it failed
+///
+/// The runner is rebuilt through cargo rather than solving in this process, since the day's code may have changed.
+fn rerun(day: &Day, input: &[String]) -> Option<Vec<PartResult>> {
+    let cargo = env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+    let output = Command::new(cargo)
+        .args([
+            "run",
+            "--quiet",
+            "--bin",
+            "aoc",
+            "--",
+            "run",
+            &day.number.to_string(),
+            "--json",
+        ])
+        .args(input)
+        .stdin(Stdio::null())
+        .stderr(Stdio::inherit())
+        .output()
+        .map_err(|e| eprintln!("Could not run cargo: {e}"))
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout.lines().map(|line| serde_json::from_str(line).ok()).collect()
+}
+
+/// Shows this run's answers and timings next to the previous run's
+fn print_comparison(current: &[PartResult], previous: Option<&[PartResult]>) {
+    println!(
+        "{:<5} {:>20} {:>12}   {:>20} {:>12}",
+        "Part", "Answer", "Time", "Previous", "Time"
+    );
+    for result in current {
+        let time = |r: &PartResult| format!("{:.2?}", Duration::from_nanos(r.elapsed_ns));
+        let before = previous.and_then(|p| p.iter().find(|r| r.part == result.part));
+        let (answer, elapsed) = before.map_or(("-".to_string(), "-".to_string()), |r| (r.answer.clone(), time(r)));
+        let changed = if before.is_some_and(|r| r.answer != result.answer) {
+            "  changed"
+        } else {
+            ""
+        };
+        println!(
+            "{:<5} {:>20} {:>12}   {answer:>20} {elapsed:>12}{changed}",
+            result.part.to_string(),
+            result.answer,
+            time(result)
+        );
+    }
+}
+
+/// Re-runs a day whenever its source or input files change, until interrupted
+pub fn watch(day: &Day, input: InputArgs) -> ExitCode {
+    let mut files = vec![];
+    let mut input_args = vec![];
+    if let Some(path) = input.input {
+        if path == Path::new(STDIN) {
+            eprintln!("Can't watch standard input; give the input's path instead.");
+            return ExitCode::FAILURE;
+        }
+        input_args = vec!["--input".to_string(), path.display().to_string()];
+        files.push(path);
+    } else if let Some(n) = input.example {
+        input_args = vec!["--example".to_string(), n.to_string()];
+    }
+    let dirs = [day_dir(day)];
+
+    println!("Watching {} for changes; press Ctrl-C to stop.", dirs[0].display());
+    let mut seen = Snapshot::new();
+    let mut previous: Option<Vec<PartResult>> = None;
+    loop {
+        let current = snapshot(&dirs, &files);
+        if current != seen {
+            seen = current;
+            println!("\n--- Day {:02} ---", day.number);
+            match rerun(day, &input_args) {
+                Some(results) => {
+                    print_comparison(&results, previous.as_deref());
+                    previous = Some(results);
+                }
+                None => println!("Failed; keeping the previous answers until the next change."),
+            }
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process;
+
+    #[test]
+    fn notices_new_and_changed_files() {
+        let dir = env::temp_dir().join(format!("aoc2023-{}-watch", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let dirs = [dir.clone()];
+
+        let empty = snapshot(&dirs, &[]);
+        fs::write(dir.join("example.txt"), "1abc2\n").unwrap();
+        let created = snapshot(&dirs, &[]);
+        assert_ne!(created, empty);
+
+        let file = fs::File::options().write(true).open(dir.join("example.txt")).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(1)).unwrap();
+        assert_ne!(snapshot(&dirs, &[]), created);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
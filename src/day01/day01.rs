@@ -1,49 +1,98 @@
+use crate::{ParseError, Solution, SolveError};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::env;
-use std::fs;
-use std::u32;
+
+const DAY: u8 = 1;
+
+/// Each digit's word, and what to replace it with so that words sharing letters, like "eightwo", both stay readable
+const DIGIT_WORDS: [(&str, &str); 9] = [
+    ("one", "o1e"),
+    ("two", "t2o"),
+    ("three", "th3ee"),
+    ("four", "f4ur"),
+    ("five", "f5ve"),
+    ("six", "s6x"),
+    ("seven", "se7en"),
+    ("eight", "ei8ht"),
+    ("nine", "n9ne"),
+];
 
 fn get_calibration(line: &str) -> u32 {
     let digits: Vec<u32> = line.chars().filter_map(|x| x.to_digit(10)).collect();
     if digits.is_empty() {
         return 0;
     }
-    return digits.first().unwrap() * 10 + digits.last().unwrap();
+    digits.first().unwrap() * 10 + digits.last().unwrap()
+}
+
+/// The calibration document, one calibration value per line
+#[derive(Serialize, Deserialize)]
+pub struct Document {
+    contents: String,
+}
+
+impl Solution for Document {
+    type Answer = u32;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        Ok(Document {
+            contents: input.to_string(),
+        })
+    }
+
+    // A line without a digit counts as 0. Lines with only spelled-out digits are still fine for part B, which is what
+    // its example is made of, so only lines with neither are reported
+    fn anomalies(input: &str) -> Vec<ParseError> {
+        input
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| {
+                !line.chars().any(|c| c.is_ascii_digit()) && !DIGIT_WORDS.iter().any(|(word, _)| line.contains(word))
+            })
+            .map(|(i, line)| ParseError::at(DAY, i + 1, line, line, "expected a digit, but this line counts as 0"))
+            .collect()
+    }
+
+    fn part_a(&self) -> Result<u32, SolveError> {
+        Ok(self.contents.lines().map(get_calibration).sum())
+    }
+
+    fn part_b(&self) -> Result<u32, SolveError> {
+        // Compose mapping from words to numerical values
+        let digit_words: HashMap<&str, &str> = HashMap::from(DIGIT_WORDS);
+
+        // Replace digit words with real digits
+        let mut new_contents = self.contents.clone();
+        for (digit, value) in digit_words.iter() {
+            new_contents = str::replace(&new_contents, digit, value);
+        }
+        Ok(new_contents.lines().map(get_calibration).sum())
+    }
 }
 
-fn main() {
-    // Compose mapping from words to numerical values
-    let digit_words: HashMap<&str, &str> = HashMap::from([
-        ("one", "o1e"),
-        ("two", "t2o"),
-        ("three", "th3ee"),
-        ("four", "f4ur"),
-        ("five", "f5ve"),
-        ("six", "s6x"),
-        ("seven", "se7en"),
-        ("eight", "ei8ht"),
-        ("nine", "n9ne"),
-    ]);
-
-    // Get command line arguments
-    let args: Vec<String> = env::args().collect();
-    assert!(args.len() == 2); // Only one argument: file name of input puzzle
-    let filename = &args[1];
-
-    // Get file contents
-    let contents = fs::read_to_string(filename).expect("Could not read file.");
-
-    // Part A
-    let sum1: u32 = contents.lines().map(get_calibration).sum();
-    println!("{}", sum1);
-
-    // Part B
-
-    // Replace digit words with real digits
-    let mut new_contents = contents;
-    for (digit, value) in digit_words.iter() {
-        new_contents = str::replace(&new_contents, digit, value);
-    }
-    let sum2: u32 = new_contents.lines().map(get_calibration).sum();
-    println!("{}", sum2)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn part_a_example() {
+        let document = Document::parse(include_str!("example.txt")).unwrap();
+        assert_eq!(document.part_a(), Ok(142));
+    }
+
+    #[test]
+    fn part_b_example() {
+        let document = Document::parse(include_str!("example2.txt")).unwrap();
+        assert_eq!(document.part_b(), Ok(281));
+    }
+
+    #[test]
+    fn lines_without_digits() {
+        assert!(Document::anomalies(include_str!("example.txt")).is_empty());
+        assert!(Document::anomalies(include_str!("example2.txt")).is_empty());
+
+        let anomalies = Document::anomalies("1abc2\npqrstuvwx\n\nseven\n");
+        let found: Vec<_> = anomalies.iter().map(|a| (a.line, a.column)).collect();
+        assert_eq!(found, [(2, 1), (3, 1)]);
+    }
 }
@@ -1,8 +1,9 @@
+use crate::{ParseError, Solution, SolveError};
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 use std::cmp;
-use std::env;
-use std::fs;
 
+const DAY: u8 = 2;
 const TARGET_SET: (u32, u32, u32) = (12, 13, 14);
 
 enum Block {
@@ -11,7 +12,8 @@ enum Block {
     Blue(u32),
 }
 
-struct Game {
+#[derive(Serialize, Deserialize)]
+pub struct Game {
     id: u32,
     maxes: (u32, u32, u32),
 }
@@ -33,12 +35,13 @@ impl Game {
         self.maxes.0 <= TARGET_SET.0 && self.maxes.1 <= TARGET_SET.1 && self.maxes.2 <= TARGET_SET.2
     }
 
-    fn power(&self) -> u32 {
-        self.maxes.0 * self.maxes.1 * self.maxes.2
+    /// Returns `None` if the power doesn't fit in 32 bits
+    fn power(&self) -> Option<u32> {
+        self.maxes.0.checked_mul(self.maxes.1)?.checked_mul(self.maxes.2)
     }
 }
 
-fn colour_count(set: &str) -> (Block, Block, Block) {
+fn colour_count(set: &str, number: usize, line: &str) -> Result<(Block, Block, Block), ParseError> {
     let red: Regex = Regex::new("(\\d+) red").unwrap();
     let green: Regex = Regex::new("(\\d+) green").unwrap();
     let blue: Regex = Regex::new("(\\d+) blue").unwrap();
@@ -46,57 +49,106 @@ fn colour_count(set: &str) -> (Block, Block, Block) {
     let mut counts: [u32; 3] = [0, 0, 0];
     for (i, pattern) in [red, green, blue].iter().enumerate() {
         if let Some(result) = pattern.captures(set) {
-            counts[i] = result.get(1).unwrap().as_str().parse().unwrap();
+            let count = result.get(1).unwrap().as_str();
+            counts[i] = count
+                .parse()
+                .map_err(|_| ParseError::at(DAY, number, line, count, "expected a cube count that fits in 32 bits"))?;
         } else {
             counts[i] = 0;
         }
     }
-    (Block::Red(counts[0]), Block::Green(counts[1]), Block::Blue(counts[2]))
+    Ok((Block::Red(counts[0]), Block::Green(counts[1]), Block::Blue(counts[2])))
 }
 
-fn main() {
-    // Get filename
-    let args: Vec<String> = env::args().collect();
-    assert!(args.len() == 2); // Only a filename
-    let filename = &args[1];
+/// The record of every game played
+#[derive(Serialize, Deserialize)]
+pub struct GameRecord {
+    games: Vec<Game>,
+}
 
-    // Read file
-    let contents = fs::read_to_string(filename).expect("Could not read file contents.");
+impl Solution for GameRecord {
+    type Answer = u32;
 
-    // Patterns
-    let game_id = Regex::new("Game (\\d+):").unwrap();
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        // Patterns
+        let game_id = Regex::new("^Game (\\d+):").unwrap();
 
-    // Parse into games
-    let games: Vec<_> = contents
-        .lines()
-        .map(|line| -> Game {
-            let mut game = Game::new(
-                game_id
+        // Parse into games
+        let games = input
+            .lines()
+            .enumerate()
+            .map(|(i, line)| -> Result<Game, ParseError> {
+                let id = game_id
                     .captures(line)
-                    .unwrap()
+                    .ok_or_else(|| {
+                        ParseError::at(DAY, i + 1, line, line, "expected `Game <id>:` at the start of the line")
+                    })?
                     .get(1)
                     .unwrap()
-                    .as_str()
-                    .parse()
-                    .unwrap(),
-            );
-
-            for set in line.split(';') {
-                let (r, g, b) = colour_count(set);
-                game.update_max(r);
-                game.update_max(g);
-                game.update_max(b);
-            }
-
-            game
-        })
-        .collect();
-
-    // Part A
-    let total: u32 = games.iter().filter(|g| g.possible()).map(|g| g.id).sum();
-    println!("{total}");
-
-    // Part B
-    let power_sum: u32 = games.iter().map(|g| g.power()).sum();
-    println!("{power_sum}")
+                    .as_str();
+                let mut game =
+                    Game::new(id.parse().map_err(|_| {
+                        ParseError::at(DAY, i + 1, line, id, "expected a game ID that fits in 32 bits")
+                    })?);
+
+                for set in line.split(';') {
+                    let (r, g, b) = colour_count(set, i + 1, line)?;
+                    game.update_max(r);
+                    game.update_max(g);
+                    game.update_max(b);
+                }
+
+                Ok(game)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(GameRecord { games })
+    }
+
+    fn part_a(&self) -> Result<u32, SolveError> {
+        self.games
+            .iter()
+            .filter(|g| g.possible())
+            .try_fold(0_u32, |total, g| total.checked_add(g.id))
+            .ok_or_else(|| SolveError::new(DAY, "the sum of the game IDs overflows 32 bits"))
+    }
+
+    fn part_b(&self) -> Result<u32, SolveError> {
+        self.games
+            .iter()
+            .try_fold(0_u32, |total, g| total.checked_add(g.power()?))
+            .ok_or_else(|| SolveError::new(DAY, "the powers of the sets of cubes overflow 32 bits"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn part_a_example() {
+        let record = GameRecord::parse(include_str!("example.txt")).unwrap();
+        assert_eq!(record.part_a(), Ok(8));
+    }
+
+    #[test]
+    fn part_b_example() {
+        let record = GameRecord::parse(include_str!("example.txt")).unwrap();
+        assert_eq!(record.part_b(), Ok(2286));
+    }
+
+    #[test]
+    fn counts_too_large_to_add_up() {
+        let record =
+            GameRecord::parse("Game 4294967295: 1 red\nGame 1: 2 blue\nGame 2: 70000 red, 70000 green, 1 blue\n")
+                .unwrap();
+        assert_eq!(
+            record.part_a().err().unwrap().message,
+            "the sum of the game IDs overflows 32 bits"
+        );
+        assert_eq!(
+            record.part_b().err().unwrap().message,
+            "the powers of the sets of cubes overflow 32 bits"
+        );
+    }
 }
@@ -1,34 +1,30 @@
-use regex::Regex;
+use crate::grid::{Coords, Grid};
+use crate::{ParseError, Solution, SolveError};
+use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
-use std::env;
-use std::fs;
+use std::ops::Range;
 
-const SURROUNDING: [(i32, i32); 8] = [(-1, 1), (-1, 0), (-1, -1), (0, -1), (0, 1), (1, -1), (1, 0), (1, 1)];
+const DAY: u8 = 3;
 
+#[derive(Clone)]
 struct Number {
     value: u32,
-    start: Coords,
-    end: Coords,
+    row: usize,
+    cols: Range<usize>,
 }
 
-type Coords = (u32, u32);
-
 impl Number {
     fn contains(&self, coords: Coords) -> bool {
-        let rows = self.start.0..self.end.0 + 1;
-        let cols = self.start.1..self.end.1;
-        rows.contains(&coords.0) && cols.contains(&coords.1)
+        coords.0 == self.row && self.cols.contains(&coords.1)
     }
 
-    fn surrounding(&self, bounds: Coords) -> HashSet<Coords> {
+    fn surrounding(&self, grid: &Grid<char>) -> HashSet<Coords> {
         let mut cells = HashSet::new();
-        for col in self.start.1..self.end.1 {
+        for col in self.cols.clone() {
             // Check all surrounding
-            for vector in SURROUNDING {
-                if let Some(translated) = translate((self.start.0, col), vector, bounds) {
-                    if !self.contains(translated) {
-                        cells.insert(translated);
-                    }
+            for neighbour in grid.neighbours8((self.row, col)) {
+                if !self.contains(neighbour) {
+                    cells.insert(neighbour);
                 }
             }
         }
@@ -36,85 +32,180 @@ impl Number {
     }
 }
 
-fn translate(coords: Coords, translation: (i32, i32), bounds: Coords) -> Option<Coords> {
-    if (coords.0 == 0 && translation.0 < 0)
-        || (coords.0 == bounds.0 && translation.0 > 0)
-        || (coords.1 == 0 && translation.1 < 0)
-        || (coords.1 == bounds.1 && translation.1 > 0)
-    {
-        return Option::None;
+/// Finds the part numbers in the grid, or the (row, column) of the first that doesn't fit in 32 bits
+fn find_numbers(grid: &Grid<char>) -> Result<Vec<Number>, Coords> {
+    let mut numbers = vec![];
+    for (row, cells) in grid.rows().enumerate() {
+        let mut col = 0;
+        for run in cells.chunk_by(|a, b| a.is_ascii_digit() == b.is_ascii_digit()) {
+            if run[0].is_ascii_digit() {
+                numbers.push(Number {
+                    value: run.iter().collect::<String>().parse().map_err(|_| (row, col))?,
+                    row,
+                    cols: col..col + run.len(),
+                });
+            }
+            col += run.len();
+        }
     }
-    Option::Some((
-        coords.0.wrapping_add_signed(translation.0),
-        coords.1.wrapping_add_signed(translation.1),
-    ))
+    Ok(numbers)
 }
 
-fn main() {
-    // Get input
-    let args: Vec<String> = env::args().collect();
-    assert!(args.len() == 2);
-    let filename = args.get(1).unwrap();
-
-    // Read file
-    let contents = fs::read_to_string(filename).expect("File could not be read.");
-
-    // Get symbol locations
-    let cols: u32 = contents.find('\n').unwrap() as u32 + 1;
-    let rows: u32 = contents.len() as u32 / cols;
-    let symbols: Vec<(char, Coords)> = contents
-        .chars()
-        .enumerate()
-        .filter(|(_, c)| *c != '.' && !c.is_ascii_digit() && !c.is_whitespace())
-        .map(|(i, c)| (c, (i as u32 / cols, i as u32 % cols)))
-        .collect();
-
-    // Get number locations
-    let numeric = Regex::new("\\d+").unwrap();
-    let numbers: Vec<_> = numeric
-        .captures_iter(&contents)
-        .map(|c| {
-            let re_match = c.get(0).unwrap();
-            Number {
-                value: re_match.as_str().parse::<u32>().expect("Expected digit."),
-                start: (re_match.start() as u32 / cols, re_match.start() as u32 % cols),
-                end: (re_match.end() as u32 / cols, re_match.end() as u32 % cols),
-            }
-        })
-        .collect();
-
-    let total: u32 = numbers
-        .iter()
-        .filter(|n| -> bool {
-            let surrounding = n.surrounding((rows, cols));
-            for (_, loc) in &symbols {
-                if surrounding.contains(loc) {
-                    return true;
+/// The engine schematic, with the location of every symbol and part number
+///
+/// Only the grid is written to JSON, since the symbols and part numbers are worked out from it.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(try_from = "Grid<char>", into = "Grid<char>")]
+pub struct Schematic {
+    grid: Grid<char>,
+    symbols: Vec<(char, Coords)>,
+    numbers: Vec<Number>,
+}
+
+impl From<Schematic> for Grid<char> {
+    fn from(schematic: Schematic) -> Self {
+        schematic.grid
+    }
+}
+
+impl TryFrom<Grid<char>> for Schematic {
+    type Error = String;
+
+    fn try_from(grid: Grid<char>) -> Result<Self, String> {
+        let numbers = find_numbers(&grid).map_err(|(row, col)| {
+            format!(
+                "the part number at row {}, column {} doesn't fit in 32 bits",
+                row + 1,
+                col + 1
+            )
+        })?;
+        Ok(Schematic::new(grid, numbers))
+    }
+}
+
+impl Schematic {
+    fn new(grid: Grid<char>, numbers: Vec<Number>) -> Self {
+        // Get symbol locations
+        let symbols: Vec<(char, Coords)> = grid
+            .iter()
+            .filter(|(_, c)| **c != '.' && !c.is_ascii_digit() && !c.is_whitespace())
+            .map(|(coords, c)| (*c, coords))
+            .collect();
+
+        Schematic { grid, symbols, numbers }
+    }
+}
+
+impl Solution for Schematic {
+    type Answer = u32;
+    const GRID: bool = true;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        let grid = Grid::parse(DAY, input, |c, _, _| Ok(c))?;
+
+        // Get number locations
+        let numbers = find_numbers(&grid).map_err(|(row, col)| {
+            let line = input.lines().nth(row).unwrap_or_default();
+            ParseError::new(
+                DAY,
+                row + 1,
+                line,
+                col + 1,
+                "expected a part number that fits in 32 bits",
+            )
+        })?;
+
+        Ok(Schematic::new(grid, numbers))
+    }
+
+    fn part_a(&self) -> Result<u32, SolveError> {
+        self.numbers
+            .iter()
+            .filter(|n| -> bool {
+                let surrounding = n.surrounding(&self.grid);
+                for (_, loc) in &self.symbols {
+                    if surrounding.contains(loc) {
+                        return true;
+                    }
                 }
-            }
-            false
-        })
-        .map(|n| n.value)
-        .sum();
-
-    println!("{total}");
-
-    // Part B
-    let gears: u32 = symbols.iter().map(|(c, l)| {
-        if *c != '*' {
-            0
-        } else {
-            let nums: Vec<_> = numbers
-                .iter()
-                .filter(|n| n.surrounding((rows, cols)).contains(l))
-                .map(|n| n.value)
-                .collect();
-            if nums.len() == 2 {
-                nums.into_iter().reduce(|acc, v| acc * v).unwrap()
-            } else {
-                0
-            }
-        }
-    }).sum();
-    println!("{gears}");
+                false
+            })
+            .try_fold(0_u32, |total, n| total.checked_add(n.value))
+            .ok_or_else(|| SolveError::new(DAY, "the sum of the part numbers overflows 32 bits"))
+    }
+
+    fn part_b(&self) -> Result<u32, SolveError> {
+        self.symbols
+            .iter()
+            .map(|(c, l)| {
+                if *c != '*' {
+                    Some(0)
+                } else {
+                    let nums: Vec<_> = self
+                        .numbers
+                        .iter()
+                        .filter(|n| n.surrounding(&self.grid).contains(l))
+                        .map(|n| n.value)
+                        .collect();
+                    if nums.len() == 2 {
+                        nums[0].checked_mul(nums[1])
+                    } else {
+                        Some(0)
+                    }
+                }
+            })
+            .try_fold(0_u32, |total, ratio| total.checked_add(ratio?))
+            .ok_or_else(|| SolveError::new(DAY, "the gear ratios overflow 32 bits"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn part_a_example() {
+        let schematic = Schematic::parse(include_str!("example.txt")).unwrap();
+        assert_eq!(schematic.part_a(), Ok(4361));
+    }
+
+    #[test]
+    fn part_b_example() {
+        let schematic = Schematic::parse(include_str!("example.txt")).unwrap();
+        assert_eq!(schematic.part_b(), Ok(467835));
+    }
+
+    #[test]
+    fn json_is_checked_like_text() {
+        let schematic = Schematic::parse("467.\n..*.\n.35.\n").unwrap();
+        let json = serde_json::to_string(&schematic).unwrap();
+        assert_eq!(json, r#"[["4","6","7","."],[".",".","*","."],[".","3","5","."]]"#);
+        let read = serde_json::from_str::<Schematic>(&json).unwrap();
+        assert_eq!((read.part_a(), read.part_b()), (Ok(502), Ok(16345)));
+
+        // Nothing worked out from the grid can be given alongside it, so it can't disagree
+        let derived =
+            r#"{"grid":[["."]],"symbols":[["*",[0,0]]],"numbers":[{"value":16,"row":1,"cols":{"start":0,"end":1}}]}"#;
+        assert!(serde_json::from_str::<Schematic>(derived).is_err());
+        let e = serde_json::from_str::<Schematic>(r#"[[".","4","2","9","4","9","6","7","2","9","6"]]"#)
+            .err()
+            .unwrap();
+        assert!(e.to_string().contains("row 1, column 2 doesn't fit in 32 bits"));
+
+        let e = Schematic::parse("..4294967296*\n").err().unwrap();
+        assert_eq!((e.line, e.column), (1, 3));
+    }
+
+    #[test]
+    fn numbers_too_large_to_add_up() {
+        let schematic = Schematic::parse("4000000000*4000000000\n").unwrap();
+        assert_eq!(
+            schematic.part_a().err().unwrap().message,
+            "the sum of the part numbers overflows 32 bits"
+        );
+        assert_eq!(
+            schematic.part_b().err().unwrap().message,
+            "the gear ratios overflow 32 bits"
+        );
+    }
 }
@@ -1,85 +1,210 @@
-use std::collections::{HashMap, HashSet};
-use std::{env, fs};
+use crate::{ParseError, Solution, SolveError};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
 
-struct Card {
+const DAY: u8 = 4;
+
+#[derive(Serialize, Deserialize)]
+pub struct Card {
     id: u32,
-    winning_nums: HashSet<u32>,
-    user_nums: HashSet<u32>,
+    winning_nums: BTreeSet<u32>,
+    user_nums: BTreeSet<u32>,
 }
 
 impl Card {
-    fn from_line(line: &str) -> Card {
+    /// Parses a card, noting any number repeated on it in `anomalies`
+    fn from_line(line: &str, number: usize, anomalies: &mut Vec<ParseError>) -> Result<Card, ParseError> {
         let binding = line.split(':').collect::<Vec<_>>();
 
         // Card ID
-        let card = binding.first().expect("No card identifier!");
-        let mut id = card.replace("Card", "");
-        id.retain(|c| !c.is_whitespace());
+        let card = binding.first().unwrap();
+        let id = card.trim_start_matches("Card").trim();
 
         // Numbers
-        let numbers = binding.get(1).expect("No numbers!");
+        let numbers = binding
+            .get(1)
+            .ok_or_else(|| ParseError::after(DAY, number, line, "expected a `:` after the card ID"))?;
         let number_sets = numbers.split('|').collect::<Vec<_>>();
-        let winners = number_sets.first().expect("Expected winning numbers");
-        let users = number_sets.get(1).expect("Expected user numbers");
-
-        Card {
-            id: id.parse().expect("Expected card ID."),
-            winning_nums: get_numbers(winners),
-            user_nums: get_numbers(users),
-        }
+        let winners = number_sets.first().unwrap();
+        let users = number_sets
+            .get(1)
+            .ok_or_else(|| ParseError::after(DAY, number, line, "expected ` | ` separator in card line"))?;
+
+        Ok(Card {
+            id: id
+                .parse()
+                .map_err(|_| ParseError::at(DAY, number, line, id, "expected a card ID"))?,
+            winning_nums: get_numbers(winners, number, line, "winning numbers", anomalies)?,
+            user_nums: get_numbers(users, number, line, "numbers you have", anomalies)?,
+        })
     }
 
     fn next_n_cards(&self) -> u32 {
         self.winning_nums.intersection(&self.user_nums).count() as u32
     }
 
-    fn points(&self) -> u32 {
+    /// One point for the first match, doubled for each match after that, or nothing if that doesn't fit in 32 bits
+    fn points(&self) -> Option<u32> {
         let winning_count = self.next_n_cards();
         if winning_count > 0 {
-            2_u32.pow(winning_count - 1)
+            2_u32.checked_pow(winning_count - 1)
         } else {
-            0
+            Some(0)
         }
     }
 }
 
-fn get_numbers(text: &str) -> HashSet<u32> {
-    text.split_whitespace()
-        .map(|n| n.parse().expect("No number in number string."))
-        .collect()
+/// Reads a set of numbers, noting in `anomalies` any that repeat, since the set only keeps one of them
+fn get_numbers(
+    text: &str,
+    number: usize,
+    line: &str,
+    set: &str,
+    anomalies: &mut Vec<ParseError>,
+) -> Result<BTreeSet<u32>, ParseError> {
+    let mut numbers = BTreeSet::new();
+    for n in text.split_whitespace() {
+        let value = n
+            .parse()
+            .map_err(|_| ParseError::at(DAY, number, line, n, "expected a number"))?;
+        if !numbers.insert(value) {
+            anomalies.push(ParseError::at(
+                DAY,
+                number,
+                line,
+                n,
+                format!("{value} is repeated among the {set}, but only counts once"),
+            ));
+        }
+    }
+    Ok(numbers)
 }
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    assert!(args.len() == 2);
-    let filename = args.get(1).unwrap();
+fn parse_cards(input: &str, anomalies: &mut Vec<ParseError>) -> Result<Vec<Card>, ParseError> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| Card::from_line(line, i + 1, anomalies))
+        .collect()
+}
 
-    let contents = fs::read_to_string(filename).expect("Could not read file.");
+/// The pile of scratchcards
+#[derive(Serialize, Deserialize)]
+pub struct Scratchcards {
+    cards: Vec<Card>,
+}
 
-    // Get cards
-    let cards: Vec<_> = contents.lines().map(Card::from_line).collect();
+impl Solution for Scratchcards {
+    type Answer = u32;
 
-    // Part A
-    let points: u32 = cards.iter().map(|c| c.points()).sum();
-    println!("{points}");
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        Ok(Scratchcards {
+            cards: parse_cards(input, &mut Vec::new())?,
+        })
+    }
 
-    // Part B
-    let mut card_counts: HashMap<u32, u32> = HashMap::new();
+    fn anomalies(input: &str) -> Vec<ParseError> {
+        let mut anomalies = Vec::new();
+        let _ = parse_cards(input, &mut anomalies);
+        anomalies
+    }
 
-    // Always one card initially
-    for card in &cards {
-        card_counts.insert(card.id, 1);
+    fn part_a(&self) -> Result<u32, SolveError> {
+        self.cards
+            .iter()
+            .try_fold(0_u32, |total, c| total.checked_add(c.points()?))
+            .ok_or_else(|| SolveError::new(DAY, "the points overflow 32 bits"))
     }
 
-    // Go through all cards
-    for card in &cards {
-        // Go through all card copies
-        for copy in card.id + 1..card.id + card.next_n_cards() + 1 {
-            let parent_card_count = *card_counts.get(&card.id).unwrap();
-            card_counts.entry(copy).and_modify(|count| *count += parent_card_count);
+    fn part_b(&self) -> Result<u32, SolveError> {
+        let mut card_counts: HashMap<u32, u32> = HashMap::new();
+
+        // Always one card initially
+        for card in &self.cards {
+            card_counts.insert(card.id, 1);
         }
+
+        // Go through all cards
+        let too_many = || SolveError::new(DAY, "the number of cards overflows 32 bits");
+        for card in &self.cards {
+            // Go through all card copies, of which there are none past the last ID there could be
+            for copy in (card.id..=card.id.saturating_add(card.next_n_cards())).skip(1) {
+                let parent_card_count = *card_counts.get(&card.id).unwrap();
+                if let Some(count) = card_counts.get_mut(&copy) {
+                    *count = count.checked_add(parent_card_count).ok_or_else(too_many)?;
+                }
+            }
+        }
+
+        card_counts
+            .values()
+            .try_fold(0_u32, |total, count| total.checked_add(*count))
+            .ok_or_else(too_many)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn part_a_example() {
+        let cards = Scratchcards::parse(include_str!("example.txt")).unwrap();
+        assert_eq!(cards.part_a(), Ok(13));
     }
 
-    let total_cards: u32 = card_counts.values().sum();
-    println!("{total_cards}");
+    #[test]
+    fn part_b_example() {
+        let cards = Scratchcards::parse(include_str!("example.txt")).unwrap();
+        assert_eq!(cards.part_b(), Ok(30));
+    }
+
+    #[test]
+    fn malformed_cards() {
+        let e = Scratchcards::parse("Card 1: 41 48 83\n").err().unwrap();
+        assert_eq!((e.line, e.column), (1, 17));
+        assert_eq!(e.message, "expected ` | ` separator in card line");
+
+        let e = Scratchcards::parse("Card 1: 41 48 | 83\nCard 2: 13 x2 | 61\n")
+            .err()
+            .unwrap();
+        assert_eq!((e.line, e.column), (2, 12));
+    }
+
+    #[test]
+    fn points_too_large_to_count() {
+        let numbers: Vec<_> = (1..=33).map(|n| n.to_string()).collect();
+        let line = format!("Card 1: {0} | {0}", numbers.join(" "));
+        let cards = Scratchcards::parse(&line).unwrap();
+        assert!(cards.part_a().is_err());
+    }
+
+    #[test]
+    fn cards_too_many_to_count() {
+        // Each card wins a copy of every card after it, doubling the count each time
+        let lines: Vec<_> = (1..=40)
+            .map(|id| {
+                let numbers: Vec<_> = (0..40 - id).map(|n| n.to_string()).collect();
+                format!("Card {id}: {0} | {0}", numbers.join(" "))
+            })
+            .collect();
+        let cards = Scratchcards::parse(&lines.join("\n")).unwrap();
+        assert_eq!(
+            cards.part_b().err().unwrap().message,
+            "the number of cards overflows 32 bits"
+        );
+
+        let last = Scratchcards::parse("Card 4294967295: 1 | 1").unwrap();
+        assert_eq!(last.part_b(), Ok(1));
+    }
+
+    #[test]
+    fn repeated_numbers() {
+        assert!(Scratchcards::anomalies(include_str!("example.txt")).is_empty());
+
+        let anomalies = Scratchcards::anomalies("Card 1: 41 48 41 | 83 86  6 31 17  9 48 53 17\n");
+        let found: Vec<_> = anomalies.iter().map(|a| (a.line, a.column)).collect();
+        assert_eq!(found, [(1, 15), (1, 44)]);
+        assert!(anomalies[0].message.contains("winning numbers"));
+    }
 }
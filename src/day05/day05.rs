@@ -1,10 +1,15 @@
+use crate::interval::IntervalSet;
+use crate::{ParseError, Solution, SolveError};
 use regex::Regex;
-use std::collections::HashMap;
-use std::env;
-use std::fs;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
 use std::ops::Range;
 
-#[derive(Eq, Hash, PartialEq, Clone, Debug)]
+const DAY: u8 = 5;
+
+#[derive(Eq, Hash, PartialEq, Ord, PartialOrd, Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 enum MapType {
     Seed,
     Soil,
@@ -17,125 +22,397 @@ enum MapType {
 }
 
 impl MapType {
-    fn from(string: &str) -> Self {
+    fn from(string: &str, number: usize, line: &str) -> Result<Self, ParseError> {
         match string {
-            "seed" => MapType::Seed,
-            "soil" => MapType::Soil,
-            "fertilizer" => MapType::Fertilizer,
-            "water" => MapType::Water,
-            "light" => MapType::Light,
-            "temperature" => MapType::Temperature,
-            "humidity" => MapType::Humidity,
-            "location" => MapType::Location,
-            _ => panic!("Unknown map type!"),
+            "seed" => Ok(MapType::Seed),
+            "soil" => Ok(MapType::Soil),
+            "fertilizer" => Ok(MapType::Fertilizer),
+            "water" => Ok(MapType::Water),
+            "light" => Ok(MapType::Light),
+            "temperature" => Ok(MapType::Temperature),
+            "humidity" => Ok(MapType::Humidity),
+            "location" => Ok(MapType::Location),
+            _ => Err(ParseError::at(
+                DAY,
+                number,
+                line,
+                string,
+                format!("unknown map category `{string}`"),
+            )),
         }
     }
 }
 
-#[derive(Debug)]
-struct Map {
-    from: MapType,
+impl fmt::Display for MapType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            MapType::Seed => "seed",
+            MapType::Soil => "soil",
+            MapType::Fertilizer => "fertilizer",
+            MapType::Water => "water",
+            MapType::Light => "light",
+            MapType::Temperature => "temperature",
+            MapType::Humidity => "humidity",
+            MapType::Location => "location",
+        };
+        write!(f, "{name}")
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Map {
     to: MapType,
     ranges: Vec<(Range<u64>, Range<u64>)>,
 }
 
 impl Map {
-    fn map(&self, origin: u64) -> (MapType, u64) {
-        for range in &self.ranges {
-            if range.1.contains(&origin) {
-                let difference = origin - range.1.start;
-                return (self.to.clone(), difference + range.0.start);
-            }
+    /// Maps every value in the set at once, splitting it wherever it crosses the edge of a range
+    fn map(&self, origin: &IntervalSet<u64>) -> (MapType, IntervalSet<u64>) {
+        let mut unmapped = origin.clone();
+        let mut mapped = IntervalSet::new();
+        for (destination, source) in &self.ranges {
+            let offset = destination.start as i128 - source.start as i128;
+            let source = IntervalSet::from(source.clone());
+            let moved = unmapped
+                .intersection(&source)
+                .shift(offset)
+                .expect("Parsing checks that ranges fit in 64 bits.");
+            mapped = mapped.union(&moved);
+            unmapped = unmapped.difference(&source);
         }
 
-        (self.to.clone(), origin)
+        (self.to.clone(), mapped.union(&unmapped))
     }
 }
 
-fn map_until_location(maps: &HashMap<MapType, Map>, seed: &u64) -> u64 {
+/// Follows the maps from seed to location; parsing checks that they get there
+fn map_until_location(maps: &BTreeMap<MapType, Map>, seeds: IntervalSet<u64>) -> IntervalSet<u64> {
     let mut cur_type = MapType::Seed;
-    let mut value = *seed;
+    let mut values = seeds;
     while cur_type != MapType::Location {
-        let map = maps.get(&cur_type).unwrap();
-        let result = map.map(value);
+        let map = &maps[&cur_type];
+        let result = map.map(&values);
         cur_type = result.0;
-        value = result.1;
+        values = result.1;
     }
-    value
+    values
 }
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    assert!(args.len() == 2);
-    let filename = args.get(1).expect("Expected passed filename.");
+/// Where following the maps from seed goes wrong before reaching location
+enum Detour {
+    /// There is no map from this category
+    Missing(MapType),
+    /// The maps lead back round to this category
+    Circle(MapType),
+}
 
-    let contents = fs::read_to_string(filename).expect("Could not open file.");
+/// Checks that every category on the way from seed to location has a map, and that they don't go round in a circle
+fn follow_route(maps: &BTreeMap<MapType, Map>) -> Result<(), Detour> {
+    let mut category = MapType::Seed;
+    let mut followed = BTreeSet::new();
+    while category != MapType::Location {
+        let map = maps.get(&category).ok_or(Detour::Missing(category.clone()))?;
+        if !followed.insert(category.clone()) {
+            return Err(Detour::Circle(category));
+        }
+        category = map.to.clone();
+    }
+    Ok(())
+}
 
+/// Parses the almanac, noting in `anomalies` any source ranges which overlap, since the first one takes precedence
+fn parse_almanac(input: &str, anomalies: &mut Vec<ParseError>) -> Result<Almanac, ParseError> {
     // Parse seeds
-    let seeds: Vec<u64> = contents
-        .lines()
-        .next()
-        .expect("No seeds.") // Get seed line
-        .split(": ")
-        .collect::<Vec<_>>()
-        .get(1) // Get space separated seeds
-        .unwrap()
+    let seed_line = input.lines().next().unwrap_or_default();
+    let seeds: Vec<u64> = seed_line
+        .strip_prefix("seeds: ")
+        .ok_or_else(|| {
+            ParseError::at(
+                DAY,
+                1,
+                seed_line,
+                seed_line,
+                "expected `seeds:` followed by the seed numbers",
+            )
+        })?
         .split(' ') // Split seed numbers
-        .map(|s| s.parse::<u64>().unwrap())
-        .collect();
+        .map(|s| {
+            s.parse::<u64>()
+                .map_err(|_| ParseError::at(DAY, 1, seed_line, s, "expected a seed number"))
+        })
+        .collect::<Result<_, _>>()?;
 
     // Parse maps
     let map_label = Regex::new("([a-z]+)-to-([a-z]+) map:").unwrap();
-    let mut maps: HashMap<MapType, Map> = HashMap::new();
+    let mut maps: BTreeMap<MapType, Map> = BTreeMap::new();
+    let mut label_lines: HashMap<MapType, (usize, &str)> = HashMap::new();
     let mut current_map: Option<MapType> = None;
-    for line in contents.lines() {
+    // The current map's source ranges so far, with the lines they are on
+    let mut sources: Vec<(Range<u64>, usize)> = vec![];
+    for (i, line) in input.lines().enumerate().skip(1) {
         // Skip blank lines
         if line.is_empty() {
             continue;
         }
 
         // Parse map label
-        if map_label.is_match(line) {
-            let labels = map_label.captures(line).unwrap().extract::<2>().1;
-            let from = MapType::from(labels[0]);
+        if let Some(captures) = map_label.captures(line) {
+            let labels = captures.extract::<2>().1;
+            let from = MapType::from(labels[0], i + 1, line)?;
             maps.insert(
                 from.clone(),
                 Map {
-                    from: from.clone(),
-                    to: MapType::from(labels[1]),
+                    to: MapType::from(labels[1], i + 1, line)?,
                     ranges: vec![],
                 },
             );
+            label_lines.insert(from.clone(), (i + 1, line));
             current_map = Some(from.clone());
+            sources.clear();
         }
         // Add the number lists to the current map
         else if let Some(ref maptype) = current_map {
-            let ranges: Vec<u64> = line.split(' ').map(|n| n.parse().unwrap()).collect();
-            let from = ranges.first().unwrap();
-            let to = ranges.get(1).unwrap();
-            let amount = ranges.get(2).unwrap();
+            let ranges: Vec<u64> = line
+                .split(' ')
+                .map(|n| {
+                    n.parse()
+                        .map_err(|_| ParseError::at(DAY, i + 1, line, n, "expected a number"))
+                })
+                .collect::<Result<_, _>>()?;
+            let [from, to, amount] = ranges[..] else {
+                return Err(ParseError::at(
+                    DAY,
+                    i + 1,
+                    line,
+                    line,
+                    "expected exactly three numbers in map line",
+                ));
+            };
+            let (Some(from_end), Some(to_end)) = (from.checked_add(amount), to.checked_add(amount)) else {
+                return Err(ParseError::at(
+                    DAY,
+                    i + 1,
+                    line,
+                    line,
+                    "expected a range that fits in 64 bits",
+                ));
+            };
+
+            let source = to..to_end;
+            if let Some((_, earlier)) = sources
+                .iter()
+                .find(|(other, _)| other.start < source.end && source.start < other.end)
+            {
+                let token = line.split(' ').nth(1).unwrap();
+                anomalies.push(ParseError::at(
+                    DAY,
+                    i + 1,
+                    line,
+                    token,
+                    format!("source range overlaps the one on line {earlier}, which is used where they overlap"),
+                ));
+            }
+            sources.push((source.clone(), i + 1));
 
             maps.entry(maptype.clone())
-                .and_modify(|m| m.ranges.push((*from..*from + *amount, *to..*to + *amount)));
+                .and_modify(|m| m.ranges.push((from..from_end, source)));
+        } else {
+            return Err(ParseError::at(
+                DAY,
+                i + 1,
+                line,
+                line,
+                "expected a `<category>-to-<category> map:` label",
+            ));
         }
     }
 
-    let locations: Vec<u64> = seeds.iter().map(|seed| map_until_location(&maps, seed)).collect();
-    println!("{}", locations.iter().min().expect("No locations found."));
-
-    // Part B
-    let min_location: u64 = seeds
-        .windows(2)
-        .enumerate()
-        .filter(|(i, _)| i % 2 == 0) // Skips windows with overlaps to just get unique pairs
-        .map(|(_, slice)| {
-            // Gets minimum location of each seed range
-            (slice[0]..slice[0] + slice[1])
-                .map(|seed| map_until_location(&maps, &seed))
-                .min()
-                .unwrap()
-        })
-        .min()
-        .unwrap();
-    println!("{min_location}");
+    match follow_route(&maps) {
+        Err(Detour::Missing(category)) => {
+            let (number, line) = input.lines().enumerate().last().map_or((1, ""), |(i, l)| (i + 1, l));
+            Err(ParseError::after(
+                DAY,
+                number,
+                line,
+                format!("expected a `{category}-to-<category> map:` label"),
+            ))
+        }
+        Err(Detour::Circle(category)) => {
+            let (number, line) = label_lines[&category];
+            Err(ParseError::at(
+                DAY,
+                number,
+                line,
+                line,
+                "expected the maps to lead to `location`, but they go round in a circle",
+            ))
+        }
+        Ok(()) => Ok(Almanac { seeds, maps }),
+    }
+}
+
+/// The almanac: the seeds to be planted and the maps from seeds to locations
+#[derive(Serialize, Deserialize)]
+#[serde(try_from = "UncheckedAlmanac")]
+pub struct Almanac {
+    seeds: Vec<u64>,
+    maps: BTreeMap<MapType, Map>,
+}
+
+/// An almanac read from JSON, before checking what parsing the text would have
+#[derive(Deserialize)]
+struct UncheckedAlmanac {
+    seeds: Vec<u64>,
+    maps: BTreeMap<MapType, Map>,
+}
+
+impl TryFrom<UncheckedAlmanac> for Almanac {
+    type Error = String;
+
+    fn try_from(almanac: UncheckedAlmanac) -> Result<Self, String> {
+        // A range is moved by its offset as a whole, so both ends must be the same length apart
+        let len = |range: &Range<u64>| range.end.checked_sub(range.start);
+        for (from, map) in &almanac.maps {
+            if let Some((destination, source)) = map.ranges.iter().find(|(d, s)| len(d).is_none() || len(d) != len(s)) {
+                return Err(format!(
+                    "the `{from}` map's destination range {destination:?} isn't the same size as its source range \
+                     {source:?}"
+                ));
+            }
+        }
+        match follow_route(&almanac.maps) {
+            Err(Detour::Missing(category)) => Err(format!("there is no map from `{category}`")),
+            Err(Detour::Circle(category)) => Err(format!("the maps go round in a circle back to `{category}`")),
+            Ok(()) => Ok(Almanac {
+                seeds: almanac.seeds,
+                maps: almanac.maps,
+            }),
+        }
+    }
+}
+
+impl Solution for Almanac {
+    type Answer = u64;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        parse_almanac(input, &mut Vec::new())
+    }
+
+    fn anomalies(input: &str) -> Vec<ParseError> {
+        let mut anomalies = Vec::new();
+        let _ = parse_almanac(input, &mut anomalies);
+        anomalies
+    }
+
+    fn part_a(&self) -> Result<u64, SolveError> {
+        let seeds = self.seeds.iter().map(|seed| *seed..seed.saturating_add(1)).collect();
+        map_until_location(&self.maps, seeds)
+            .min()
+            .ok_or_else(|| SolveError::new(DAY, "expected at least one seed"))
+    }
+
+    // The seed ranges are far too big to map one seed at a time, so whole ranges are mapped
+    // together and only split where they straddle the edge of a map's range
+    fn part_b(&self) -> Result<u64, SolveError> {
+        // Any list of seeds will do for part A, so pairing them up is only a problem for this part
+        if self.seeds.len() % 2 == 1 {
+            return Err(SolveError::new(
+                DAY,
+                format!(
+                    "expected the seeds in pairs of a start and a length, but there are {}",
+                    self.seeds.len()
+                ),
+            ));
+        }
+        let seeds = self
+            .seeds
+            .chunks_exact(2)
+            .map(|pair| pair[0]..pair[0].saturating_add(pair[1]))
+            .collect();
+        map_until_location(&self.maps, seeds)
+            .min()
+            .ok_or_else(|| SolveError::new(DAY, "expected at least one seed range with a length above zero"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn part_a_example() {
+        let almanac = Almanac::parse(include_str!("example.txt")).unwrap();
+        assert_eq!(almanac.part_a(), Ok(35));
+    }
+
+    #[test]
+    fn part_b_example() {
+        let almanac = Almanac::parse(include_str!("example.txt")).unwrap();
+        assert_eq!(almanac.part_b(), Ok(46));
+    }
+
+    #[test]
+    fn unknown_map_category() {
+        let e = Almanac::parse("seeds: 79 14\n\nseed-to-soyl map:\n50 98 2\n")
+            .err()
+            .unwrap();
+        assert_eq!((e.line, e.column), (3, 9));
+        assert_eq!(e.message, "unknown map category `soyl`");
+    }
+
+    #[test]
+    fn seeds_come_in_pairs() {
+        let odd = Almanac::parse("seeds: 7 79 7\n\nseed-to-location map:\n50 98 2\n").unwrap();
+        assert_eq!(odd.part_a(), Ok(7));
+        assert_eq!(
+            odd.part_b().err().unwrap().message,
+            "expected the seeds in pairs of a start and a length, but there are 3"
+        );
+
+        // Every seed on its own is still a seed for part A, but no range of seeds is
+        let empty = Almanac::parse("seeds: 5 0\n\nseed-to-location map:\n50 98 2\n").unwrap();
+        assert_eq!(empty.part_a(), Ok(0));
+        assert!(empty.part_b().err().unwrap().message.contains("length above zero"));
+    }
+
+    #[test]
+    fn maps_must_reach_location() {
+        let missing = "seeds: 79 14\n\nseed-to-soil map:\n50 98 2\n";
+        let e = Almanac::parse(missing).err().unwrap();
+        assert_eq!((e.line, e.column), (4, 8));
+        assert!(e.message.contains("`soil-to-<category> map:`"));
+
+        let circle = "seeds: 79 14\n\nseed-to-soil map:\n50 98 2\n\nsoil-to-seed map:\n1 2 3\n";
+        let e = Almanac::parse(circle).err().unwrap();
+        assert_eq!(e.line, 3);
+        assert!(e.message.contains("round in a circle"));
+    }
+
+    #[test]
+    fn overlapping_source_ranges() {
+        assert!(Almanac::anomalies(include_str!("example.txt")).is_empty());
+
+        let overlapping = include_str!("example.txt").replace("52 50 48", "52 50 49");
+        let anomalies = Almanac::anomalies(&overlapping);
+        let found: Vec<_> = anomalies.iter().map(|a| (a.line, a.column)).collect();
+        assert_eq!(found, [(5, 4)]);
+        assert!(anomalies[0].message.contains("line 4"));
+    }
+
+    #[test]
+    fn json_is_checked_like_text() {
+        let almanac = Almanac::parse(include_str!("example.txt")).unwrap();
+        let json = serde_json::to_string(&almanac).unwrap();
+        assert!(json.starts_with(r#"{"seeds":[79,14,55,13],"maps":{"seed":{"to":"soil","ranges":[[{"start":50"#));
+
+        let circle = json.replace(r#""humidity":{"to":"location""#, r#""humidity":{"to":"seed""#);
+        let e = serde_json::from_str::<Almanac>(&circle).err().unwrap();
+        assert!(e.to_string().contains("circle back to `seed`"));
+        let uneven = json.replacen(r#"{"start":50,"end":52}"#, r#"{"start":50,"end":53}"#, 1);
+        assert!(serde_json::from_str::<Almanac>(&uneven).is_err());
+        let odd = json.replacen("[79,14,55,13]", "[79,14,55]", 1);
+        let odd = serde_json::from_str::<Almanac>(&odd).unwrap();
+        assert_eq!(odd.part_a(), Ok(43));
+        assert!(odd.part_b().err().unwrap().message.contains("in pairs"));
+    }
 }
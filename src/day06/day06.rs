@@ -1,48 +1,240 @@
+use crate::math::isqrt;
+use crate::{ParseError, Solution, SolveError};
 use core::iter::zip;
-use std::env;
-use std::fs;
+use serde::{Deserialize, Serialize};
 
-fn parse_numbers(text: &str) -> Vec<u64> {
+const DAY: u8 = 6;
+
+fn parse_numbers(text: &str, number: usize, line: &str) -> Result<Vec<u64>, ParseError> {
     text.split_whitespace()
-        .map(|n| n.parse().expect("Expected number."))
+        .map(|n| {
+            n.parse()
+                .map_err(|_| ParseError::at(DAY, number, line, n, "expected a number"))
+        })
         .collect()
 }
 
-fn distance(hold_time: u64, total_time: u64) -> u64 {
-    hold_time * (total_time - hold_time)
+/// Reads a line of the race sheet, returning the line and the numbers after its label
+fn parse_line<'a>(line: Option<&'a str>, number: usize, label: &str) -> Result<(&'a str, &'a str), ParseError> {
+    let line = line.ok_or_else(|| ParseError::after(DAY, number, "", format!("expected a `{label}` line")))?;
+    let numbers = line.strip_prefix(label).ok_or_else(|| {
+        ParseError::at(
+            DAY,
+            number,
+            line,
+            line,
+            format!("expected `{label}` at the start of the line"),
+        )
+    })?;
+    Ok((line, numbers))
+}
+
+/// Reads the numbers as a single number, ignoring the spaces between them, unless there are none or it doesn't fit in
+/// 64 bits
+fn kerned(numbers: &[u64]) -> Option<u64> {
+    numbers.iter().map(u64::to_string).collect::<String>().parse().ok()
+}
+
+fn distance(hold_time: u64, total_time: u64) -> u128 {
+    hold_time as u128 * (total_time - hold_time) as u128
+}
+
+/// Counts the ways of holding the button that beat the record
+///
+/// The distance travelled is a downward parabola in the hold time, symmetric about half the race, so the winning hold
+/// times are the whole numbers strictly between its two crossings of the record. The square root only gets the first
+/// of those close; it is then nudged onto the exact boundary.
+fn ways_to_win(time: u64, record: u64) -> u64 {
+    let t = time as u128;
+    let Some(discriminant) = (t * t).checked_sub(4 * record as u128) else {
+        return 0;
+    };
+    let mut first = (time - isqrt(discriminant).min(time)) / 2;
+    while first > 0 && distance(first - 1, time) > record as u128 {
+        first -= 1;
+    }
+    while first <= time / 2 && distance(first, time) <= record as u128 {
+        first += 1;
+    }
+    if first > time / 2 {
+        0
+    } else {
+        time - 2 * first + 1
+    }
+}
+
+/// The race sheet, read both as separate races and as one race with bad kerning
+///
+/// Only the separate races are written to JSON, since the one race is worked out from them.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(try_from = "UncheckedRaces", into = "UncheckedRaces")]
+pub struct Races {
+    times: Vec<u64>,
+    distances: Vec<u64>,
+    time: u64,
+    dist: u64,
+}
+
+/// The separate races read from JSON, before checking what parsing the text would have
+#[derive(Serialize, Deserialize)]
+struct UncheckedRaces {
+    times: Vec<u64>,
+    distances: Vec<u64>,
+}
+
+impl From<Races> for UncheckedRaces {
+    fn from(races: Races) -> Self {
+        UncheckedRaces {
+            times: races.times,
+            distances: races.distances,
+        }
+    }
 }
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    assert!(args.len() == 2);
-    let filename = args.get(1).expect("Expected puzzle input.");
+impl TryFrom<UncheckedRaces> for Races {
+    type Error = String;
+
+    fn try_from(races: UncheckedRaces) -> Result<Self, String> {
+        if races.times.len() != races.distances.len() {
+            return Err(format!(
+                "expected a distance for each of the {} races, but there are {}",
+                races.times.len(),
+                races.distances.len()
+            ));
+        }
+        let kerned = |numbers: &[u64], what: &str| {
+            kerned(numbers).ok_or_else(|| format!("expected {what} that fit in 64 bits when read as one number"))
+        };
+        Ok(Races {
+            time: kerned(&races.times, "times")?,
+            dist: kerned(&races.distances, "distances")?,
+            times: races.times,
+            distances: races.distances,
+        })
+    }
+}
+
+impl Solution for Races {
+    type Answer = u64;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        let mut lines = input.lines();
+
+        let (time_line, raw_times) = parse_line(lines.next(), 1, "Time:")?;
+        let (distance_line, raw_distances) = parse_line(lines.next(), 2, "Distance:")?;
+        let times = parse_numbers(raw_times, 1, time_line)?;
+        let distances = parse_numbers(raw_distances, 2, distance_line)?;
+        if times.len() != distances.len() {
+            return Err(ParseError::after(
+                DAY,
+                2,
+                distance_line,
+                format!(
+                    "expected a distance for each of the {} races, but there are {}",
+                    times.len(),
+                    distances.len()
+                ),
+            ));
+        }
+
+        let kerned = |numbers: &[u64], number: usize, line: &str, text: &str| {
+            kerned(numbers)
+                .ok_or_else(|| ParseError::at(DAY, number, line, text, "expected digits that fit in 64 bits"))
+        };
+        Ok(Races {
+            time: kerned(&times, 1, time_line, raw_times)?,
+            dist: kerned(&distances, 2, distance_line, raw_distances)?,
+            times,
+            distances,
+        })
+    }
+
+    fn part_a(&self) -> Result<u64, SolveError> {
+        zip(&self.times, &self.distances)
+            .map(|(t, d)| ways_to_win(*t, *d))
+            .try_fold(1_u64, |acc, e| acc.checked_mul(e))
+            .ok_or_else(|| SolveError::new(DAY, "the product of the ways to win overflows 64 bits"))
+    }
+
+    fn part_b(&self) -> Result<u64, SolveError> {
+        Ok(ways_to_win(self.time, self.dist))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn part_a_example() {
+        let races = Races::parse(include_str!("example.txt")).unwrap();
+        assert_eq!(races.part_a(), Ok(288));
+    }
+
+    #[test]
+    fn part_b_example() {
+        let races = Races::parse(include_str!("example.txt")).unwrap();
+        assert_eq!(races.part_b(), Ok(71503));
+    }
 
-    let contents = fs::read_to_string(filename).expect("Could not read file.");
-    let mut lines = contents.lines();
+    #[test]
+    fn races_without_records() {
+        let e = Races::parse("Time:      7  15   30\nDistance:  9  40\n").err().unwrap();
+        assert_eq!((e.line, e.column), (2, 17));
+        assert_eq!(
+            e.message,
+            "expected a distance for each of the 3 races, but there are 2"
+        );
+        let e = Races::parse("Time:\nDistance:\n").err().unwrap();
+        assert_eq!((e.line, e.column), (1, 6));
+    }
 
-    let raw_times = &lines.next().expect("Expected times.").replace("Time: ", "");
-    let raw_distances = &lines.next().expect("Expected distances.").replace("Distance: ", "");
+    #[test]
+    fn json_is_checked_like_text() {
+        let races = Races::parse(include_str!("example.txt")).unwrap();
+        let json = serde_json::to_string(&races).unwrap();
+        assert_eq!(json, r#"{"times":[7,15,30],"distances":[9,40,200]}"#);
+        assert_eq!(serde_json::from_str::<Races>(&json).unwrap().part_b(), Ok(71503));
 
-    // Part A
-    let times = parse_numbers(raw_times);
-    let distances = parse_numbers(raw_distances);
+        let e = serde_json::from_str::<Races>(r#"{"times":[7,15],"distances":[9]}"#)
+            .err()
+            .unwrap();
+        assert!(e.to_string().contains("for each of the 2 races"));
+        assert!(serde_json::from_str::<Races>(r#"{"times":[],"distances":[]}"#).is_err());
+        assert!(serde_json::from_str::<Races>(r#"{"times":[1844674407370955161],"distances":[5]}"#).is_ok());
+        assert!(serde_json::from_str::<Races>(r#"{"times":[1844674407370955161,6],"distances":[5,1]}"#).is_err());
+    }
 
-    let winning_options = zip(&times, &distances)
-        .map(|(t, d)| (0..*t).filter(|time| distance(*time, *t) > *d).count() as u64)
-        .reduce(|acc, e| acc * e)
-        .unwrap();
+    #[test]
+    fn unbeatable_records() {
+        assert_eq!(ways_to_win(0, 0), 0);
+        assert_eq!(ways_to_win(6, 9), 0);
+        assert_eq!(ways_to_win(6, 8), 1);
+        assert_eq!(ways_to_win(7, 12), 0);
+    }
 
-    println!("{winning_options}");
+    // proptest is a host-only dev-dependency, so the property tests are left out of WebAssembly builds
+    #[cfg(not(target_arch = "wasm32"))]
+    mod properties {
+        use super::*;
+        use proptest::prelude::*;
 
-    // Part B
-    let mut time_str = raw_times.clone();
-    time_str.retain(|c| c.is_ascii_digit());
-    let time: u64 = time_str.parse().unwrap();
+        /// Tries every hold time
+        fn ways_to_win_by_scanning(time: u64, record: u64) -> u64 {
+            (0..time).filter(|t| distance(*t, time) > record as u128).count() as u64
+        }
 
-    let mut distance_str = raw_distances.clone();
-    distance_str.retain(|c| c.is_ascii_digit());
-    let dist: u64 = distance_str.parse().unwrap();
+        proptest! {
+            #[test]
+            fn ways_to_win_matches_scanning(time in 0..5_000u64, record in 0..7_000_000u64) {
+                prop_assert_eq!(ways_to_win(time, record), ways_to_win_by_scanning(time, record));
+            }
 
-    let option_count = (0..time).filter(|t| distance(*t, time) > dist).count() as u64;
-    println!("{option_count}");
+            #[test]
+            fn ways_to_win_near_the_best_distance(time in 0..5_000u64, below in 0..50u64) {
+                let record = (time / 2 * (time - time / 2)).saturating_sub(below);
+                prop_assert_eq!(ways_to_win(time, record), ways_to_win_by_scanning(time, record));
+            }
+        }
+    }
 }
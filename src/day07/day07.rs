@@ -1,9 +1,12 @@
+use crate::{ParseError, Solution, SolveError};
+use serde::{Deserialize, Serialize};
 use std::cmp::{Eq, Ordering};
 use std::collections::HashMap;
-use std::env;
-use std::fs;
 use std::iter::zip;
 
+const DAY: u8 = 7;
+const HAND_SIZE: usize = 5;
+
 #[derive(PartialEq, Eq, PartialOrd, Ord, Debug)]
 enum HandType {
     FiveOfAKind = 6,
@@ -15,7 +18,7 @@ enum HandType {
     HighCard = 0,
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 enum Card {
     Joker,
     Number(u8),
@@ -25,8 +28,8 @@ enum Card {
     Ace,
 }
 
-#[derive(Debug)]
-struct Hand {
+#[derive(Debug, Clone)]
+pub struct Hand {
     cards: Vec<Card>,
     bid: u32,
 }
@@ -121,63 +124,211 @@ impl Ord for Hand {
     }
 }
 
-fn char_to_card(c: char, wildcards: bool) -> Card {
+impl Card {
+    /// The card's label, as written in a hand
+    fn label(&self) -> char {
+        match self {
+            Card::Ace => 'A',
+            Card::King => 'K',
+            Card::Queen => 'Q',
+            Card::Jack | Card::Joker => 'J',
+            Card::Number(10) => 'T',
+            Card::Number(n) => char::from(b'0' + n),
+        }
+    }
+}
+
+/// Reads a card from its label, which is a joker rather than a jack when playing with wildcards
+fn card_from_label(c: char, wildcards: bool) -> Option<Card> {
     match c {
-        'A' => Card::Ace,
-        'K' => Card::King,
-        'Q' => Card::Queen,
+        'A' => Some(Card::Ace),
+        'K' => Some(Card::King),
+        'Q' => Some(Card::Queen),
         'J' => {
             if wildcards {
-                Card::Joker
+                Some(Card::Joker)
             } else {
-                Card::Jack
+                Some(Card::Jack)
             }
         }
-        'T' => Card::Number(10),
-        _ => Card::Number(c.to_digit(10).expect("Expected digit.").try_into().unwrap()),
+        'T' => Some(Card::Number(10)),
+        '2'..='9' => c.to_digit(10).map(|n| Card::Number(n as u8)),
+        _ => None,
     }
 }
 
-fn calc_winnings(hands: &[Hand]) -> u32 {
-    hands.iter().enumerate().map(|(i, h)| (i + 1) as u32 * h.bid).sum()
+fn char_to_card(c: char, wildcards: bool, number: usize, line: &str, column: usize) -> Result<Card, ParseError> {
+    card_from_label(c, wildcards).ok_or_else(|| {
+        ParseError::new(
+            DAY,
+            number,
+            line,
+            column,
+            "expected a card label (A, K, Q, J, T or 2-9)",
+        )
+    })
 }
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    assert!(args.len() == 2);
-    let filename = args.get(1).expect("Expected a filename.");
-
-    let contents = fs::read_to_string(filename).expect("Could not read file.");
+fn calc_winnings(hands: &[Hand]) -> Result<u32, SolveError> {
+    hands
+        .iter()
+        .enumerate()
+        .try_fold(0_u32, |total, (i, h)| {
+            u32::try_from(i + 1).ok()?.checked_mul(h.bid)?.checked_add(total)
+        })
+        .ok_or_else(|| SolveError::new(DAY, "the total winnings overflow 32 bits"))
+}
 
-    // Part A
-    let mut hands: Vec<Hand> = contents
+fn parse_hands(input: &str, wildcards: bool) -> Result<Vec<Hand>, ParseError> {
+    let mut hands: Vec<Hand> = input
         .lines()
-        .map(|l| {
-            let (cards, bid) = l.split_once(' ').expect("Expected hand and bid!");
-            Hand::new(
-                bid.parse().unwrap(),
-                cards.chars().map(|c| char_to_card(c, false)).collect(),
-            )
+        .enumerate()
+        .map(|(i, l)| {
+            let (cards, bid) = l
+                .split_once(' ')
+                .ok_or_else(|| ParseError::after(DAY, i + 1, l, "expected a hand and bid separated by a space"))?;
+            if cards.chars().count() != HAND_SIZE {
+                return Err(ParseError::at(
+                    DAY,
+                    i + 1,
+                    l,
+                    cards,
+                    format!("expected {HAND_SIZE} cards in hand"),
+                ));
+            }
+            Ok(Hand::new(
+                bid.parse()
+                    .map_err(|_| ParseError::at(DAY, i + 1, l, bid, "expected a bid"))?,
+                cards
+                    .chars()
+                    .enumerate()
+                    .map(|(j, c)| char_to_card(c, wildcards, i + 1, l, j + 1))
+                    .collect::<Result<_, _>>()?,
+            ))
         })
-        .collect();
+        .collect::<Result<_, _>>()?;
     hands.sort();
+    Ok(hands)
+}
 
-    let winnings: u32 = calc_winnings(&hands);
-    println!("{winnings}");
+/// Every hand and its bid, ranked once with jacks and once with jokers
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(try_from = "HandList", into = "HandList")]
+pub struct CamelCards {
+    hands: Vec<Hand>,
+    wild_card_hands: Vec<Hand>,
+}
 
-    // Part B
-    let mut wild_card_hands: Vec<Hand> = contents
-        .lines()
-        .map(|l| {
-            let (cards, bid) = l.split_once(' ').expect("Expected hand and bid!");
-            Hand::new(
-                bid.parse().unwrap(),
-                cards.chars().map(|c| char_to_card(c, true)).collect(),
-            )
+/// The hands as written in JSON, each with its cards' labels and its bid
+#[derive(Serialize, Deserialize)]
+struct HandList {
+    hands: Vec<LabelledHand>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct LabelledHand {
+    cards: String,
+    bid: u32,
+}
+
+impl From<CamelCards> for HandList {
+    fn from(game: CamelCards) -> Self {
+        let hands = game
+            .hands
+            .iter()
+            .map(|hand| LabelledHand {
+                cards: hand.cards.iter().map(Card::label).collect(),
+                bid: hand.bid,
+            })
+            .collect();
+        HandList { hands }
+    }
+}
+
+impl TryFrom<HandList> for CamelCards {
+    type Error = String;
+
+    fn try_from(list: HandList) -> Result<Self, String> {
+        let ranked = |wildcards| -> Result<Vec<Hand>, String> {
+            let mut hands = list
+                .hands
+                .iter()
+                .map(|hand| {
+                    if hand.cards.chars().count() != HAND_SIZE {
+                        return Err(format!("expected {HAND_SIZE} cards in hand `{}`", hand.cards));
+                    }
+                    let cards = hand
+                        .cards
+                        .chars()
+                        .map(|c| {
+                            card_from_label(c, wildcards)
+                                .ok_or_else(|| format!("`{c}` in hand `{}` is not a card label", hand.cards))
+                        })
+                        .collect::<Result<_, _>>()?;
+                    Ok(Hand::new(hand.bid, cards))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            hands.sort();
+            Ok(hands)
+        };
+        Ok(CamelCards {
+            hands: ranked(false)?,
+            wild_card_hands: ranked(true)?,
         })
-        .collect();
-    wild_card_hands.sort();
+    }
+}
 
-    let wild_card_winnings = calc_winnings(&wild_card_hands);
-    println!("{wild_card_winnings}");
+impl Solution for CamelCards {
+    type Answer = u32;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        Ok(CamelCards {
+            hands: parse_hands(input, false)?,
+            wild_card_hands: parse_hands(input, true)?,
+        })
+    }
+
+    fn part_a(&self) -> Result<u32, SolveError> {
+        calc_winnings(&self.hands)
+    }
+
+    fn part_b(&self) -> Result<u32, SolveError> {
+        calc_winnings(&self.wild_card_hands)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn part_a_example() {
+        let game = CamelCards::parse(include_str!("example.txt")).unwrap();
+        assert_eq!(game.part_a(), Ok(6440));
+    }
+
+    #[test]
+    fn part_b_example() {
+        let game = CamelCards::parse(include_str!("example.txt")).unwrap();
+        assert_eq!(game.part_b(), Ok(5905));
+    }
+
+    #[test]
+    fn unknown_card_labels() {
+        for (hand, column) in [("32T1K 765", 4), ("T55J0 684", 5), ("KK6x7 28", 4)] {
+            let e = CamelCards::parse(hand).err().unwrap();
+            assert_eq!((e.line, e.column), (1, column), "{hand}");
+            assert!(e.message.contains("2-9"));
+        }
+    }
+
+    #[test]
+    fn winnings_too_large_to_count() {
+        let game = CamelCards::parse("32T3K 4294967295\nKTJJT 2\n").unwrap();
+        assert_eq!(
+            game.part_a().err().unwrap().message,
+            "the total winnings overflow 32 bits"
+        );
+        assert!(game.part_b().is_err());
+    }
 }
@@ -1,85 +1,322 @@
+use crate::graph::{Graph, NodeId};
+use crate::math;
+use crate::{ParseError, Solution, SolveError};
 use regex::Regex;
-use std::collections::HashMap;
-use std::env;
-use std::fs;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+const DAY: u8 = 8;
 
 const START_NODE: &str = "AAA";
 const END_NODE: &str = "ZZZ";
 
-fn parse_node(line: &str) -> (&str, (&str, &str)) {
-    let pattern = Regex::new("([A-Z]{3})").unwrap();
+fn parse_node(line: &str, number: usize) -> Result<(&str, (&str, &str)), ParseError> {
+    let pattern = Regex::new("([0-9A-Z]{3})").unwrap();
     let captures: Vec<_> = pattern.find_iter(line).collect();
-    let name = captures.get(0).expect("Expected node name.");
-    let left = captures.get(1).expect("Expected left node.");
-    let right = captures.get(2).expect("Expected right node.");
-    (name.as_str(), (left.as_str(), right.as_str()))
+    let name = captures
+        .first()
+        .ok_or_else(|| ParseError::after(DAY, number, line, "expected a node name"))?;
+    let left = captures
+        .get(1)
+        .ok_or_else(|| ParseError::after(DAY, number, line, "expected a left node"))?;
+    let right = captures
+        .get(2)
+        .ok_or_else(|| ParseError::after(DAY, number, line, "expected a right node"))?;
+    Ok((name.as_str(), (left.as_str(), right.as_str())))
 }
 
-fn next_step<'a>(node: &str, direction: Option<char>, graph: &'a HashMap<&'a str, (&'a str, &'a str)>) -> &'a str {
-    let nodes = graph.get(node).unwrap();
-    match direction {
-        Some('R') => nodes.1,
-        Some('L') => nodes.0,
-        _ => panic!("Expected Right or Left direction."),
-    }
+fn next_step(node: NodeId, direction: Option<char>, graph: &Graph<String, char>) -> NodeId {
+    let (next, _) = graph
+        .edges(node)
+        .iter()
+        .find(|(_, turn)| Some(*turn) == direction)
+        .expect("Expected Right or Left direction.");
+    *next
 }
 
+/// Counts the steps from `start` to the first node meeting `condition`, or nothing if the directions never lead to one
+///
+/// After as many steps as there are nodes times directions, some node must have been reached twice at the same point
+/// in the directions, so from then on the walk only goes round in a circle.
 fn steps_until_destination(
-    start: &str,
+    start: NodeId,
     directions: &str,
-    graph: &HashMap<&str, (&str, &str)>,
+    graph: &Graph<String, char>,
     condition: fn(&str) -> bool,
-) -> u64 {
+) -> Option<u64> {
+    let limit = graph.len() as u64 * directions.len() as u64;
     let mut steps: u64 = 0;
     let mut direction_cycle = directions.chars().cycle();
     let mut node = start;
-    while !condition(node) {
+    while !condition(&graph[node]) {
+        if steps == limit {
+            return None;
+        }
         node = next_step(node, direction_cycle.next(), graph);
         steps += 1;
     }
-    steps
+    Some(steps)
+}
+
+/// Builds the network from each node's left and right nodes, labelling each edge with the turn that takes it
+fn connect<'a>(nodes: impl IntoIterator<Item = (&'a str, (&'a str, &'a str))>) -> Graph<String, char> {
+    let mut graph = Graph::directed();
+    for (node, (left, right)) in nodes {
+        graph.add_edge(node.to_string(), left.to_string(), 'L');
+        graph.add_edge(node.to_string(), right.to_string(), 'R');
+    }
+    graph
+}
+
+/// The map documents: the left/right instructions and the network of nodes, with each edge labelled by its turn
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(try_from = "Documents", into = "Documents")]
+pub struct Network {
+    directions: String,
+    graph: Graph<String, char>,
+}
+
+/// The map documents as written in JSON, with the nodes to the left and right of each node
+#[derive(Serialize, Deserialize)]
+struct Documents {
+    directions: String,
+    nodes: BTreeMap<String, (String, String)>,
+}
+
+impl From<Network> for Documents {
+    fn from(network: Network) -> Self {
+        let graph = &network.graph;
+        let turn = |id, direction| {
+            let (next, _) = graph
+                .edges(id)
+                .iter()
+                .find(|(_, turn)| *turn == direction)
+                .expect("Every node has both turns.");
+            graph[*next].clone()
+        };
+        let nodes = graph
+            .nodes()
+            .map(|(id, name)| (name.clone(), (turn(id, 'L'), turn(id, 'R'))))
+            .collect();
+        Documents {
+            directions: network.directions,
+            nodes,
+        }
+    }
 }
 
-fn gcd(mut x: u64, mut y: u64) -> u64 {
-    while y != 0 {
-        (x, y) = (y, x % y)
+impl TryFrom<Documents> for Network {
+    type Error = String;
+
+    fn try_from(documents: Documents) -> Result<Self, String> {
+        if documents.directions.is_empty() || documents.directions.contains(|c| c != 'L' && c != 'R') {
+            return Err("expected one or more `L` or `R` directions".to_string());
+        }
+        for (node, (left, right)) in &documents.nodes {
+            if let Some(missing) = [left, right].into_iter().find(|n| !documents.nodes.contains_key(*n)) {
+                return Err(format!(
+                    "node `{node}` leads to `{missing}`, which isn't in the network"
+                ));
+            }
+        }
+
+        let nodes = documents
+            .nodes
+            .iter()
+            .map(|(node, (left, right))| (node.as_str(), (left.as_str(), right.as_str())));
+        Ok(Network {
+            graph: connect(nodes),
+            directions: documents.directions,
+        })
     }
-    x
 }
 
-fn main() {
-    let args: Vec<_> = env::args().collect();
-    assert!(args.len() == 2);
-    let filename = args.get(1).expect("Expected puzzle input file.");
+impl Solution for Network {
+    type Answer = u64;
 
-    let contents = fs::read_to_string(filename).expect("Could not open file.");
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        let mut lines = input.lines();
+        let directions = lines.next().unwrap_or_default();
+        if directions.is_empty() {
+            return Err(ParseError::after(DAY, 1, directions, "expected at least one direction"));
+        }
+        if let Some((i, _)) = directions.char_indices().find(|(_, c)| *c != 'L' && *c != 'R') {
+            return Err(ParseError::at(
+                DAY,
+                1,
+                directions,
+                &directions[i..],
+                "expected an `L` or `R` direction",
+            ));
+        }
+        if let Some(line) = lines.next().filter(|l| !l.is_empty()) {
+            return Err(ParseError::at(
+                DAY,
+                2,
+                line,
+                line,
+                "expected a blank line after the directions",
+            ));
+        }
 
-    let mut lines = contents.lines();
-    let directions = lines.next().expect("Expected directions.");
-    lines.next().expect("Expected blank line.");
+        let nodes = lines
+            .enumerate()
+            .map(|(i, line)| Ok((i + 3, line, parse_node(line, i + 3)?)))
+            .collect::<Result<Vec<_>, ParseError>>()?;
 
-    let mut graph = HashMap::new();
-    for line in lines {
-        let (node, nodes) = parse_node(line);
-        graph.insert(node, nodes);
+        // Every node that can be turned to needs its own line saying where to go from there
+        let defined: HashSet<&str> = nodes.iter().map(|(_, _, (node, _))| *node).collect();
+        for (number, line, (_, (left, right))) in &nodes {
+            if let Some(missing) = [left, right].into_iter().find(|n| !defined.contains(*n)) {
+                return Err(ParseError::at(
+                    DAY,
+                    *number,
+                    line,
+                    missing,
+                    format!("expected node `{missing}` to have a line of its own"),
+                ));
+            }
+        }
+
+        Ok(Network {
+            directions: directions.to_string(),
+            graph: connect(nodes.into_iter().map(|(_, _, node)| node)),
+        })
     }
 
-    // Part A
-    let steps = steps_until_destination(START_NODE, directions, &graph, |n: &str| n == END_NODE);
-    println!("{steps}");
+    // The part B example has no `AAA`, so a missing start is only a problem for this part
+    fn part_a(&self) -> Result<u64, SolveError> {
+        let start = self
+            .graph
+            .id(START_NODE)
+            .ok_or_else(|| SolveError::new(DAY, format!("expected a node `{START_NODE}` to start from")))?;
+        steps_until_destination(start, &self.directions, &self.graph, |n: &str| n == END_NODE).ok_or_else(|| {
+            SolveError::new(
+                DAY,
+                format!("the directions never lead from {START_NODE} to {END_NODE}"),
+            )
+        })
+    }
 
-    // Part B
-    let start_nodes = Vec::from_iter(graph.keys().filter(|k| k.ends_with('A')).copied());
-    let is_end_node = |n: &str| n.ends_with('Z'); // Test if a node is destination
-    let multi_steps: Vec<_> = start_nodes
-        .iter()
-        .map(|n| steps_until_destination(n, directions, &graph, is_end_node))
-        .collect();
+    fn part_b(&self) -> Result<u64, SolveError> {
+        let start_nodes = Vec::from_iter(self.graph.nodes().filter(|(_, n)| n.ends_with('A')).map(|(id, _)| id));
+        if start_nodes.is_empty() {
+            return Err(SolveError::new(DAY, "expected a node ending in A to start from"));
+        }
+        let is_end_node = |n: &str| n.ends_with('Z'); // Test if a node is destination
+        let multi_steps = start_nodes
+            .iter()
+            .map(|n| {
+                steps_until_destination(*n, &self.directions, &self.graph, is_end_node).ok_or_else(|| {
+                    SolveError::new(
+                        DAY,
+                        format!(
+                            "the directions never lead from {} to a node ending in Z",
+                            self.graph[*n]
+                        ),
+                    )
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        math::lcm_all(multi_steps).map_err(|e| {
+            SolveError::new(
+                DAY,
+                format!("the step counts' lowest common multiple doesn't fit in 64 bits: {e}"),
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn part_a_example() {
+        let network = Network::parse(include_str!("example.txt")).unwrap();
+        assert_eq!(network.part_a(), Ok(2));
+    }
+
+    #[test]
+    fn part_a_repeated_directions() {
+        let network = Network::parse(include_str!("example2.txt")).unwrap();
+        assert_eq!(network.part_a(), Ok(6));
+    }
+
+    #[test]
+    fn part_b_example() {
+        let network = Network::parse(include_str!("example3.txt")).unwrap();
+        assert_eq!(network.part_b(), Ok(6));
+    }
+
+    #[test]
+    fn missing_start_nodes() {
+        let network = Network::parse(include_str!("example3.txt")).unwrap();
+        let e = network.part_a().unwrap_err();
+        assert!(e.message.contains("`AAA`"));
+
+        let network = Network::parse("L\n\nBBB = (ZZZ, ZZZ)\nZZZ = (ZZZ, ZZZ)\n").unwrap();
+        assert!(network.part_b().is_err());
+    }
+
+    #[test]
+    fn step_counts_too_large_to_combine() {
+        // One start for each prime up to 53, taking that many steps to reach its end, whose product needs 65 bits
+        const LETTERS: &[u8] = b"0123456789BCDEFGHIJKLMNOPQRSTUVWXY";
+        let mut names = (0..).map(|n: usize| [n / 34 / 34, n / 34 % 34, n % 34].map(|i| LETTERS[i] as char));
+        let mut input = "L\n\n".to_string();
+        let primes = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53];
+        for (chain, steps) in primes.into_iter().enumerate() {
+            let mut node = format!("{chain:02}A");
+            for _ in 1..steps {
+                let next: String = names.next().unwrap().iter().collect();
+                input += &format!("{node} = ({next}, {next})\n");
+                node = next;
+            }
+            input += &format!("{node} = ({chain:02}Z, {chain:02}Z)\n{chain:02}Z = ({chain:02}Z, {chain:02}Z)\n");
+        }
+
+        let network = Network::parse(&input).unwrap();
+        let e = network.part_b().unwrap_err();
+        assert!(e.message.contains("overflow"), "{e}");
+    }
+
+    #[test]
+    fn digit_node_names() {
+        assert_eq!(parse_node("11A = (11B, XXX)", 3).unwrap(), ("11A", ("11B", "XXX")));
+        let network = Network::parse(include_str!("example3.txt")).unwrap();
+        let names: Vec<_> = network.graph.nodes().map(|(_, name)| name.as_str()).collect();
+        assert!(names.contains(&"22Z") && names.contains(&"XXX"));
+    }
+
+    #[test]
+    fn unreachable_destinations_end() {
+        let network = Network::parse("LR\n\nAAA = (BBB, BBB)\nBBB = (AAA, AAA)\nZZZ = (ZZZ, ZZZ)\n").unwrap();
+        let start = network.graph.id("AAA").unwrap();
+        let is_end = |n: &str| n == END_NODE;
+        assert_eq!(steps_until_destination(start, "LR", &network.graph, is_end), None);
+    }
 
-    let mut cycles_collide = 1;
-    for step in multi_steps {
-        cycles_collide = cycles_collide * step / gcd(cycles_collide, step);
+    #[test]
+    fn every_node_needs_a_line() {
+        let e = Network::parse("L\n\nAAA = (BBB, ZZZ)\nZZZ = (ZZZ, ZZZ)\n")
+            .err()
+            .unwrap();
+        assert_eq!((e.line, e.column), (3, 8));
     }
 
-    println!("{cycles_collide}");
+    #[test]
+    fn json_lists_each_nodes_turns() {
+        let network = Network::parse(include_str!("example2.txt")).unwrap();
+        let json = serde_json::to_string(&network).unwrap();
+        assert_eq!(
+            json,
+            r#"{"directions":"LLR","nodes":{"AAA":["BBB","BBB"],"BBB":["AAA","ZZZ"],"ZZZ":["ZZZ","ZZZ"]}}"#
+        );
+        assert_eq!(serde_json::from_str::<Network>(&json).unwrap().part_a(), Ok(6));
+
+        let missing = json.replace(r#""ZZZ":["ZZZ","ZZZ"]"#, r#""ZZZ":["ZZZ","YYY"]"#);
+        assert!(serde_json::from_str::<Network>(&missing).is_err());
+    }
 }
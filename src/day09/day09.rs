@@ -1,53 +1,130 @@
-use std::env;
-use std::fs;
+use crate::{ParseError, Solution, SolveError};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug)]
-struct Readings {
+const DAY: u8 = 9;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Readings {
     raw: Vec<i64>,
 }
 
 impl Readings {
-    fn next_val(&self) -> i64 {
+    /// Returns `None` if a difference or the extrapolated value doesn't fit in 64 bits
+    fn next_val(&self) -> Option<i64> {
         let mut finals: Vec<i64> = vec![];
         let mut working_set: Vec<i64> = self.raw.to_vec();
         while !working_set.iter().all(|n| *n == 0) {
             finals.push(*working_set.last().expect("Expected at least one digit."));
-            working_set = working_set.windows(2).map(|w| w[1] - w[0]).collect();
+            working_set = differences(&working_set)?;
         }
-        finals.iter().sum()
+        finals.iter().try_fold(0_i64, |acc, e| acc.checked_add(*e))
     }
 
-    fn prev_val(&self) -> i64 {
+    /// Returns `None` if a difference or the extrapolated value doesn't fit in 64 bits
+    fn prev_val(&self) -> Option<i64> {
         let mut prevs: Vec<i64> = vec![];
         let mut working_set: Vec<i64> = self.raw.to_vec();
         while !working_set.iter().all(|n| *n == 0) {
             prevs.push(*working_set.first().expect("Expected at least one digit."));
-            working_set = working_set.windows(2).map(|w| w[1] - w[0]).collect();
+            working_set = differences(&working_set)?;
         }
-        prevs.push(0);
-        prevs.iter().rev().copied().reduce(|acc, e| e - acc).unwrap()
+        prevs.iter().rev().try_fold(0_i64, |acc, e| e.checked_sub(acc))
     }
 }
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    assert!(args.len() == 2);
-    let filename = args.get(1).expect("Expected filename.");
+/// The differences between each pair of neighbouring values, unless one doesn't fit in 64 bits
+fn differences(values: &[i64]) -> Option<Vec<i64>> {
+    values.windows(2).map(|w| w[1].checked_sub(w[0])).collect()
+}
+
+/// Adds up the extrapolated values, explaining which reading couldn't be extrapolated
+fn sum_extrapolated(readings: &[Readings], extrapolate: impl Fn(&Readings) -> Option<i64>) -> Result<i64, SolveError> {
+    readings.iter().enumerate().try_fold(0_i64, |total, (i, r)| {
+        extrapolate(r)
+            .ok_or_else(|| SolveError::new(DAY, format!("the values on line {} don't fit in 64 bits", i + 1)))?
+            .checked_add(total)
+            .ok_or_else(|| SolveError::new(DAY, "the sum of the extrapolated values overflows 64 bits"))
+    })
+}
 
-    let contents = fs::read_to_string(filename).expect("Could not read file.");
+/// The report of every value's history
+#[derive(Serialize, Deserialize)]
+pub struct Report {
+    readings: Vec<Readings>,
+}
 
-    let readings: Vec<Readings> = contents
-        .lines()
-        .map(|l| Readings {
-            raw: l.split(' ').map(|n| n.parse().expect("Expected number.")).collect(),
-        })
-        .collect();
+impl Solution for Report {
+    type Answer = i64;
 
-    // Part A
-    let next_value_sum: i64 = readings.iter().map(|r| r.next_val()).sum();
-    println!("{next_value_sum}");
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        let readings: Vec<Readings> = input
+            .lines()
+            .enumerate()
+            .map(|(i, l)| {
+                Ok(Readings {
+                    raw: l
+                        .split(' ')
+                        .map(|n| {
+                            n.parse()
+                                .map_err(|_| ParseError::at(DAY, i + 1, l, n, "expected a number"))
+                        })
+                        .collect::<Result<_, _>>()?,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Report { readings })
+    }
+
+    fn part_a(&self) -> Result<i64, SolveError> {
+        sum_extrapolated(&self.readings, Readings::next_val)
+    }
+
+    fn part_b(&self) -> Result<i64, SolveError> {
+        sum_extrapolated(&self.readings, Readings::prev_val)
+    }
+}
 
-    // Part B
-    let prev_value_sum: i64 = readings.iter().map(|r| r.prev_val()).sum();
-    println!("{prev_value_sum}");
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn part_a_example() {
+        let report = Report::parse(include_str!("example.txt")).unwrap();
+        assert_eq!(report.part_a(), Ok(114));
+    }
+
+    #[test]
+    fn part_b_example() {
+        let report = Report::parse(include_str!("example.txt")).unwrap();
+        assert_eq!(report.part_b(), Ok(2));
+    }
+
+    #[test]
+    fn values_too_large_to_extrapolate() {
+        let report = Report::parse("0 9223372036854775807\n").unwrap();
+        assert_eq!(
+            report.part_a().err().unwrap().message,
+            "the values on line 1 don't fit in 64 bits"
+        );
+        let report = Report::parse("9223372036854775807 -9223372036854775808\n").unwrap();
+        assert!(report.part_b().is_err());
+        let report = Report::parse("9223372036854775807\n9223372036854775807\n").unwrap();
+        assert!(report.part_a().err().unwrap().message.contains("sum"));
+    }
+
+    // Needs proptest, which is only a dev-dependency off WebAssembly
+    #[cfg(not(target_arch = "wasm32"))]
+    mod properties {
+        use super::*;
+        use proptest::prelude::*;
+
+        proptest! {
+            #[test]
+            fn prev_val_matches_next_val_reversed(raw in prop::collection::vec(-1_000i64..1_000, 1..20)) {
+                let reversed = Readings { raw: raw.iter().rev().copied().collect() };
+                prop_assert_eq!(Readings { raw }.prev_val(), reversed.next_val());
+            }
+        }
+    }
 }
@@ -1,37 +1,12 @@
-use core::panic;
-use std::collections::HashMap;
-use std::env;
-use std::fs;
-
-const DIRECTIONS: [(Direction, (i32, i32)); 4] = [
-    (Direction::North, (-1, 0)),
-    (Direction::South, (1, 0)),
-    (Direction::East, (0, 1)),
-    (Direction::West, (0, -1)),
-];
-
-type Coords = (u32, u32);
-
-#[derive(PartialEq, Eq, Clone, Debug)]
-enum Direction {
-    North,
-    South,
-    East,
-    West,
-}
+use crate::graph::{Graph, NodeId};
+use crate::grid::{Coords, Direction, Grid};
+use crate::{ParseError, Solution, SolveError};
+use serde::{Deserialize, Serialize};
 
-impl Direction {
-    fn opposite(&self) -> Self {
-        match self {
-            Self::North => Self::South,
-            Self::South => Self::North,
-            Self::East => Self::West,
-            Self::West => Self::East,
-        }
-    }
-}
+const DAY: u8 = 10;
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 enum Pipe {
     Vertical,
     Horizontal,
@@ -43,23 +18,27 @@ enum Pipe {
     Start,
 }
 
-impl From<char> for Pipe {
-    fn from(value: char) -> Self {
+impl Pipe {
+    fn from(value: char, number: usize, line: &str, column: usize) -> Result<Self, ParseError> {
         match value {
-            '.' => Self::Ground,
-            'S' => Self::Start,
-            '|' => Self::Vertical,
-            '-' => Self::Horizontal,
-            'L' => Self::NorthEast,
-            'J' => Self::NorthWest,
-            '7' => Self::SouthWest,
-            'F' => Self::SouthEast,
-            _ => panic!("Unknown pipe type {value}!"),
+            '.' => Ok(Self::Ground),
+            'S' => Ok(Self::Start),
+            '|' => Ok(Self::Vertical),
+            '-' => Ok(Self::Horizontal),
+            'L' => Ok(Self::NorthEast),
+            'J' => Ok(Self::NorthWest),
+            '7' => Ok(Self::SouthWest),
+            'F' => Ok(Self::SouthEast),
+            _ => Err(ParseError::new(
+                DAY,
+                number,
+                line,
+                column,
+                "expected a pipe tile (|, -, L, J, 7, F, . or S)",
+            )),
         }
     }
-}
 
-impl Pipe {
     fn ends(&self) -> Vec<Direction> {
         match self {
             Self::Ground => vec![],
@@ -83,113 +62,204 @@ impl Pipe {
     }
 }
 
-#[derive(Debug)]
-struct Connection {
-    direction: Direction,
-    loc: Coords,
+/// The field of pipes, as a graph of the pipes which connect to each other
+///
+/// Only the tiles are written to JSON, since the graph is worked out from them.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(try_from = "Grid<Pipe>", into = "Grid<Pipe>")]
+pub struct PipeMaze {
+    tiles: Grid<Pipe>,
+    graph: Graph<Coords>,
+    start: NodeId,
 }
 
-impl Connection {
-    fn new(direction: Direction, loc: Coords) -> Self {
-        Connection { direction, loc }
+impl From<PipeMaze> for Grid<Pipe> {
+    fn from(maze: PipeMaze) -> Self {
+        maze.tiles
     }
 }
 
-type Connections = Vec<Connection>;
-
-fn translate(coord: Coords, vector: (i32, i32), bounds: Coords) -> Option<Coords> {
-    if coord.0 == 0 && vector.0 < 0
-        || coord.1 == 0 && vector.1 < 0
-        || coord.0 == bounds.0 && vector.0 > 0
-        || coord.1 == bounds.1 && vector.1 > 0
-    {
-        None
-    } else {
-        Some((
-            coord.0.checked_add_signed(vector.0).expect("Overflow occurred."),
-            coord.1.checked_add_signed(vector.1).expect("Overlow occurred."),
-        ))
+impl TryFrom<Grid<Pipe>> for PipeMaze {
+    type Error = String;
+
+    fn try_from(tiles: Grid<Pipe>) -> Result<Self, String> {
+        // As when parsing, the last start tile is the one used
+        let (start, _) = tiles
+            .iter()
+            .filter(|(_, pipe)| **pipe == Pipe::Start)
+            .last()
+            .ok_or("expected a start tile")?;
+        connect(tiles, start).map_err(|message| format!("{message}, at row {}, column {}", start.0 + 1, start.1 + 1))
     }
 }
 
-fn loop_length(graph: &HashMap<Coords, Connections>, start: &Coords) -> u32 {
-    let mut counter = 1;
-    let mut prev_pos = *start;
-    let mut cur_pos = graph.get(start).unwrap().first().unwrap().loc;
-
-    loop {
-        for pos in graph.get(&cur_pos).unwrap() {
-            if pos.loc != prev_pos {
-                prev_pos = cur_pos;
-                cur_pos = pos.loc;
-                break;
+/// Connects the pipes, returning what's wrong if the start tile at `start` isn't on a loop
+fn connect(tiles: Grid<Pipe>, start: Coords) -> Result<PipeMaze, &'static str> {
+    // Build graph of pipes which connect, looking only south and east so each connection is added once
+    let mut graph = Graph::undirected();
+    for (loc, pipe) in tiles.iter() {
+        for direction in [Direction::South, Direction::East] {
+            if let Some(new_coords) = tiles
+                .step(loc, direction)
+                .filter(|n| pipe.connects(&tiles[*n], direction))
+            {
+                graph.add_edge(loc, new_coords, ());
             }
         }
+    }
 
-        if cur_pos == *start {
-            break;
-        }
-        counter += 1;
+    let start_id = graph.id(&start).ok_or("expected pipes connecting to the start tile")?;
+    if graph.find_cycle(start_id).is_none() {
+        return Err("expected the start tile to be on a loop");
     }
-    counter
+    Ok(PipeMaze {
+        tiles,
+        graph,
+        start: start_id,
+    })
 }
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    assert!(args.len() == 2);
-    let filename = args.get(1).expect("Expected puzzle input file.");
-
-    let contents = fs::read_to_string(filename).expect("Could not read file.");
-
-    let mut counter = contents.lines();
-    let bounds = (
-        counter.next().expect("Expected at least one line.").len() as u32,
-        counter.count() as u32 + 1,
-    );
-
-    let mut grid: HashMap<Coords, Pipe> = HashMap::new();
-    let mut start = (0, 0);
-    for (i, line) in contents.lines().enumerate() {
-        for (j, c) in line.chars().enumerate() {
-            grid.insert((i as u32, j as u32), c.into());
-            if Pipe::from(c) == Pipe::Start {
-                start = (i as u32, j as u32)
-            }
-        }
+impl PipeMaze {
+    /// Returns every tile on the loop, in the order they are visited from the start tile
+    fn loop_tiles(&self) -> Vec<Coords> {
+        let tiles = self.graph.find_cycle(self.start).expect("Parsing checks for a loop.");
+        tiles.into_iter().map(|id| self.graph[id]).collect()
     }
+}
 
-    // Build graph of pipes which connect
-    let mut graph: HashMap<Coords, Connections> = HashMap::new();
-    for (loc, pipe) in &grid {
-        for (direction, vector) in &DIRECTIONS {
-            if let Some(new_coords) = translate(*loc, *vector, bounds) {
-                if let Some(p) = grid.get(&new_coords) {
-                    if pipe.connects(p, direction.clone()) {
-                        graph
-                            .entry(*loc)
-                            .and_modify(|c| c.push(Connection::new(direction.clone(), new_coords)))
-                            .or_insert(vec![Connection::new(direction.clone(), new_coords)]);
-                    }
-                }
-            }
+/// Parses the field of pipes, noting in `anomalies` every start tile but the last, which is the one used
+fn parse_maze(input: &str, anomalies: &mut Vec<ParseError>) -> Result<PipeMaze, ParseError> {
+    let mut starts = vec![];
+    let grid = Grid::parse(DAY, input, |c, coords, line| {
+        let pipe = Pipe::from(c, coords.0 + 1, line, coords.1 + 1)?;
+        if pipe == Pipe::Start {
+            starts.push(coords)
         }
+        Ok(pipe)
+    })?;
+    // Only the last start tile is used
+    for (row, col) in &starts[..starts.len().saturating_sub(1)] {
+        anomalies.push(ParseError::new(
+            DAY,
+            row + 1,
+            input.lines().nth(*row).unwrap(),
+            col + 1,
+            "expected only one start tile `S`, but this one is ignored for a later one",
+        ));
     }
+    let start = starts.last().copied().ok_or_else(|| {
+        ParseError::new(
+            DAY,
+            1,
+            input.lines().next().unwrap_or_default(),
+            1,
+            "expected a start tile `S`",
+        )
+    })?;
 
-    // Seems like the start node will only have two valid connections, so a position on the loop is
-    // always known
-    //
-    // It also seems that each node on the loop has two connections: where you came from and where
-    // you can go. There are no "sub-loops". So you can traverse the graph until you reach the
-    // start node again and label each spot with a distance value, and then take the maximum
+    let line = input.lines().nth(start.0).unwrap();
+    connect(grid, start).map_err(|message| ParseError::new(DAY, start.0 + 1, line, start.1 + 1, message))
+}
+
+impl Solution for PipeMaze {
+    type Answer = u32;
+    const GRID: bool = true;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        parse_maze(input, &mut Vec::new())
+    }
+
+    fn anomalies(input: &str) -> Vec<ParseError> {
+        let mut anomalies = Vec::new();
+        let _ = parse_maze(input, &mut anomalies);
+        anomalies
+    }
+
+    // Each tile on the loop has two connections: where you came from and where you can go. Pipes
+    // outside the loop can still point into the start tile, so only the loop itself is walked
     //
     // Loops have a unique property such that the furthest point on the loop from any other point
     // is equal to the loop length divided by two
+    fn part_a(&self) -> Result<u32, SolveError> {
+        Ok(self.loop_tiles().len() as u32 / 2)
+    }
+
+    // The loop is a simple polygon whose vertices are the tile centres, so the shoelace formula
+    // gives its area. Pick's theorem then relates that area to the number of tiles enclosed:
+    // A = i + b/2 - 1, where b is the number of tiles on the loop itself
+    fn part_b(&self) -> Result<u32, SolveError> {
+        let tiles = self.loop_tiles();
+        let twice_area: i64 = tiles
+            .iter()
+            .zip(tiles.iter().cycle().skip(1))
+            .map(|(a, b)| a.0 as i64 * b.1 as i64 - b.0 as i64 * a.1 as i64)
+            .sum();
+        Ok((twice_area.abs() / 2 - tiles.len() as i64 / 2 + 1) as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn part_a_square_loop() {
+        let maze = PipeMaze::parse(include_str!("example.txt")).unwrap();
+        assert_eq!(maze.part_a(), Ok(4));
+    }
+
+    #[test]
+    fn part_a_complex_loop() {
+        let maze = PipeMaze::parse(include_str!("example2.txt")).unwrap();
+        assert_eq!(maze.part_a(), Ok(8));
+    }
+
+    #[test]
+    fn part_a_junk_pointing_into_start() {
+        // The pipes to the west of `S` lead into it, but aren't part of the loop
+        let maze = PipeMaze::parse(".......\n----S7.\n....||.\n....LJ.\n.......\n").unwrap();
+        assert_eq!(maze.part_a(), Ok(3));
+    }
+
+    #[test]
+    fn part_b_enclosed_loop() {
+        let maze = PipeMaze::parse(include_str!("example3.txt")).unwrap();
+        assert_eq!(maze.part_b(), Ok(4));
+    }
+
+    #[test]
+    fn part_b_larger_loop() {
+        let maze = PipeMaze::parse(include_str!("example4.txt")).unwrap();
+        assert_eq!(maze.part_b(), Ok(8));
+    }
+
+    #[test]
+    fn part_b_junk_pipes() {
+        let maze = PipeMaze::parse(include_str!("example5.txt")).unwrap();
+        assert_eq!(maze.part_b(), Ok(10));
+    }
+
+    #[test]
+    fn part_b_small_loops() {
+        let maze = PipeMaze::parse("S7\nLJ\n").unwrap();
+        assert_eq!(maze.part_b(), Ok(0));
+        let maze = PipeMaze::parse(".....\n.S-7.\n.|.|.\n.L-J.\n.....\n").unwrap();
+        assert_eq!(maze.part_b(), Ok(1));
+    }
+
+    #[test]
+    fn unknown_tiles() {
+        let e = PipeMaze::parse("S7\nLX\n").err().unwrap();
+        assert_eq!((e.line, e.column), (2, 2));
+        assert!(e.message.starts_with("expected a pipe tile"));
+    }
+
+    #[test]
+    fn several_start_tiles() {
+        assert!(PipeMaze::anomalies(include_str!("example.txt")).is_empty());
 
-    // Part A
-    let length = loop_length(&graph, &start);
-    if length % 2 == 0 {
-        println!("{}", length / 2);
-    } else {
-        println!("{}", length / 2 + 1);
+        let anomalies = PipeMaze::anomalies("S....\n.S-7.\n.|.|.\n.L-J.\n.....\n");
+        let found: Vec<_> = anomalies.iter().map(|a| (a.line, a.column)).collect();
+        assert_eq!(found, [(1, 1)]);
     }
 }
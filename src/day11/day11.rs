@@ -1,95 +1,251 @@
-use std::env;
-use std::fs;
+use crate::grid::Grid;
+use crate::{ParseError, Solution, SolveError};
+use serde::{Deserialize, Serialize};
+
+const DAY: u8 = 11;
 
 /// Represents a galaxy in the puzzle input
-#[derive(Debug)]
-struct Galaxy {
+#[derive(Debug, Clone)]
+pub struct Galaxy {
     x: usize,
     y: usize,
 }
 
 impl Galaxy {
-    /// Calculates the Manhattan distance between two galaxies
-    fn manhattan_distance(&self, other: &Galaxy) -> usize {
-        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    /// Calculates the Manhattan distance between two galaxies, unless it doesn't fit in a `usize`
+    fn manhattan_distance(&self, other: &Galaxy) -> Option<usize> {
+        self.x.abs_diff(other.x).checked_add(self.y.abs_diff(other.y))
     }
 }
 
-/// Takes a list of galaxies and maps them to an expanded galaxy list
+/// Takes a list of galaxies and maps them to an expanded galaxy list, unless the expanded image doesn't fit in a
+/// `usize`, which is only 32 bits in the browser
 fn expanded_galaxies(
     galaxies: &[Galaxy],
     empty_rows: &[usize],
     empty_cols: &[usize],
     expansion_factor: usize,
-) -> Vec<Galaxy> {
+) -> Option<Vec<Galaxy>> {
     assert!(expansion_factor > 1);
+    let expand = |at: usize, empty: &[usize]| {
+        let before = empty.iter().filter(|e| at > **e).count();
+        before.checked_mul(expansion_factor - 1)?.checked_add(at)
+    };
     galaxies
         .iter()
-        .map(|g| Galaxy {
-            y: g.y + empty_rows.iter().filter(|r| g.y > **r).count() * (expansion_factor - 1),
-            x: g.x + empty_cols.iter().filter(|c| g.x > **c).count() * (expansion_factor - 1),
+        .map(|g| {
+            Some(Galaxy {
+                y: expand(g.y, empty_rows)?,
+                x: expand(g.x, empty_cols)?,
+            })
         })
         .collect()
 }
 
-/// Returns a list of the Manhattan distances between each pair of galaxies
-fn galaxy_distances(galaxies: &[Galaxy]) -> Vec<usize> {
-    let mut distances: Vec<usize> = Vec::new();
+/// Returns the sum of the Manhattan distances between each pair of galaxies, unless it doesn't fit in a `usize`
+fn total_distance(galaxies: &[Galaxy]) -> Option<usize> {
+    let mut total: usize = 0;
     for i in 0..galaxies.len() {
         let g1 = &galaxies[i];
         for g2 in galaxies.iter().skip(i + 1) {
-            distances.push(g1.manhattan_distance(g2));
+            total = total.checked_add(g1.manhattan_distance(g2)?)?;
         }
     }
-    distances
+    Some(total)
 }
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    assert!(args.len() == 2);
-    let filename = args.get(1).expect("Expected puzzle input file.");
-    let contents = fs::read_to_string(filename).expect("Could not read file.");
-
-    let n_rows = contents.lines().count();
-    let n_cols = contents.lines().next().expect("At least one line").chars().count();
-
-    // Parse galaxies from the puzzle input
-    let galaxies: Vec<_> = contents
-        .lines()
-        .enumerate()
-        .flat_map(|(y, l)| {
-            l.chars()
-                .enumerate()
-                .filter(|(_, c)| *c == '#')
-                .map(move |(x, _)| Galaxy { x, y })
-        })
-        .collect();
+/// Expands the image by `expansion_factor`, then adds up the distances between its galaxies
+fn expanded_distance(image: &Image, expansion_factor: usize) -> Result<usize, SolveError> {
+    expanded_galaxies(&image.galaxies, &image.empty_rows, &image.empty_cols, expansion_factor)
+        .and_then(|galaxies| total_distance(&galaxies))
+        .ok_or_else(|| SolveError::new(DAY, "the distances in the expanded image don't fit in a `usize`"))
+}
+
+/// The image of the universe, with the rows and columns that expand
+///
+/// Only the image, `true` wherever there is a galaxy, is written to JSON, since the rest is worked out from it.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(from = "Grid<bool>", into = "Grid<bool>")]
+pub struct Image {
+    sky: Grid<bool>,
+    galaxies: Vec<Galaxy>,
+    empty_rows: Vec<usize>,
+    empty_cols: Vec<usize>,
+}
+
+impl From<Image> for Grid<bool> {
+    fn from(image: Image) -> Self {
+        image.sky
+    }
+}
+
+impl From<Grid<bool>> for Image {
+    fn from(sky: Grid<bool>) -> Self {
+        let galaxies: Vec<_> = sky
+            .iter()
+            .filter(|(_, galaxy)| **galaxy)
+            .map(|((y, x), _)| Galaxy { x, y })
+            .collect();
+
+        // Find the rows and columns which are empty (due to the lack of galaxies)
+        let empty_rows: Vec<_> = sky
+            .rows()
+            .enumerate()
+            .filter(|(_, row)| !row.contains(&true))
+            .map(|(r, _)| r)
+            .collect();
+
+        let empty_cols: Vec<_> = sky
+            .columns()
+            .enumerate()
+            .filter_map(|(c, mut col)| (!col.any(|g| *g)).then_some(c))
+            .collect();
+
+        Image {
+            sky,
+            galaxies,
+            empty_rows,
+            empty_cols,
+        }
+    }
+}
+
+impl Solution for Image {
+    type Answer = usize;
+    const GRID: bool = true;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        // `true` wherever there is a galaxy
+        let sky = Grid::parse(DAY, input, |c, (row, col), line| match c {
+            '#' => Ok(true),
+            '.' => Ok(false),
+            _ => Err(ParseError::new(
+                DAY,
+                row + 1,
+                line,
+                col + 1,
+                "expected a galaxy `#` or empty space `.`",
+            )),
+        })?;
+        Ok(Image::from(sky))
+    }
+
+    fn part_a(&self) -> Result<usize, SolveError> {
+        // Any galaxy with a row index (y) greater than an empty row's index will have its row index (y)
+        // incremented by one. Any galaxy with a column index (x) greater than an empty column's index
+        // will have its column index (x) incremented by one. Then calculate the shortest distance
+        // between each pair of galaxies.
 
-    // Find the rows and columns which are empty (due to the lack of galaxies)
-    let empty_rows: Vec<_> = (0..n_rows)
-        .filter(|r| galaxies.iter().map(|g| g.y).filter(|y| y == r).count() == 0)
-        .collect();
+        expanded_distance(self, 2)
+    }
 
-    let empty_cols: Vec<_> = (0..n_cols)
-        .filter(|c| galaxies.iter().map(|g| g.x).filter(|x| x == c).count() == 0)
-        .collect();
+    fn part_b(&self) -> Result<usize, SolveError> {
+        // For part two we do the same, but use a bigger expansion factor
 
-    // Any galaxy with a row index (y) greater than an empty row's index will have its row index (y)
-    // incremented by one. Any galaxy with a column index (x) greater than an empty column's index
-    // will have its column index (x) incremented by one.
+        expanded_distance(self, 1000000)
+    }
+}
 
-    let doubled_galaxy = expanded_galaxies(&galaxies, &empty_rows, &empty_cols, 2);
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    // Now calculate the shortest distance between each pair of galaxies
+    #[test]
+    fn part_a_example() {
+        let image = Image::parse(include_str!("example.txt")).unwrap();
+        assert_eq!(image.part_a(), Ok(374));
+    }
 
-    let double_distances = galaxy_distances(&doubled_galaxy);
+    #[test]
+    fn part_b_example() {
+        let image = Image::parse(include_str!("example.txt")).unwrap();
+        assert_eq!(image.part_b(), Ok(82000210));
+    }
 
-    println!("{}", double_distances.iter().sum::<usize>());
+    #[test]
+    fn smaller_expansion_factors() {
+        let image = Image::parse(include_str!("example.txt")).unwrap();
+        for (factor, total) in [(10, 1030), (100, 8410)] {
+            assert_eq!(expanded_distance(&image, factor), Ok(total));
+        }
+    }
 
-    // For part two we do the same, but use a bigger expansion factor
+    #[test]
+    fn json_is_worked_out_like_text() {
+        let image = Image::parse("#..\n...\n..#\n").unwrap();
+        let json = serde_json::to_string(&image).unwrap();
+        assert_eq!(json, "[[true,false,false],[false,false,false],[false,false,true]]");
+        let read = serde_json::from_str::<Image>(&json).unwrap();
+        assert_eq!((&read.empty_rows[..], &read.empty_cols[..]), (&[1][..], &[1][..]));
+        assert_eq!(read.part_a(), Ok(6));
+        assert!(serde_json::from_str::<Image>("[[true,false],[false]]").is_err());
+        // Nothing worked out from the image can be given alongside it, so it can't disagree
+        let derived = r#"{"galaxies":[{"x":0,"y":0},{"x":3,"y":0}],"empty_rows":[],"empty_cols":[0,1,2]}"#;
+        assert!(serde_json::from_str::<Image>(derived).is_err());
+    }
 
-    let large_galaxy = expanded_galaxies(&galaxies, &empty_rows, &empty_cols, 1000000);
-    let large_distances = galaxy_distances(&large_galaxy);
+    #[test]
+    fn expansion_too_large_to_measure() {
+        let image = Image::parse("#..\n...\n..#\n").unwrap();
+        assert!(expanded_galaxies(&image.galaxies, &image.empty_rows, &image.empty_cols, usize::MAX).is_none());
+        let far = [Galaxy { x: 0, y: 0 }, Galaxy { x: usize::MAX, y: 1 }];
+        assert_eq!(total_distance(&far), None);
+        assert!(expanded_distance(&image, usize::MAX).is_err());
+    }
+
+    // Off WebAssembly only, like the proptest dev-dependency
+    #[cfg(not(target_arch = "wasm32"))]
+    mod properties {
+        use super::*;
+        use proptest::prelude::*;
+
+        /// Expands the image by copying each empty row and column until it is `factor` wide, then measures directly
+        fn distances_by_inserting(image: &[Vec<bool>], factor: usize) -> usize {
+            let mut rows = vec![];
+            for row in image {
+                let copies = if row.contains(&true) { 1 } else { factor };
+                rows.extend(std::iter::repeat_n(row.clone(), copies));
+            }
+            let empty_cols: Vec<bool> = (0..image[0].len()).map(|c| image.iter().all(|row| !row[c])).collect();
+            let expanded: Vec<Vec<bool>> = rows
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .zip(&empty_cols)
+                        .flat_map(|(galaxy, empty)| std::iter::repeat_n(*galaxy, if *empty { factor } else { 1 }))
+                        .collect()
+                })
+                .collect();
+
+            let mut galaxies = vec![];
+            for (y, row) in expanded.iter().enumerate() {
+                galaxies.extend(
+                    row.iter()
+                        .enumerate()
+                        .filter(|(_, g)| **g)
+                        .map(|(x, _)| Galaxy { x, y }),
+                );
+            }
+            total_distance(&galaxies).unwrap()
+        }
 
-    println!("{}", large_distances.iter().sum::<usize>());
+        /// Images of up to 12 by 12, sparse enough to have empty rows and columns
+        fn image() -> impl Strategy<Value = Vec<Vec<bool>>> {
+            (1..12usize, 1..12usize).prop_flat_map(|(width, height)| {
+                prop::collection::vec(prop::collection::vec(prop::bool::weighted(0.15), width), height)
+            })
+        }
+
+        proptest! {
+            #[test]
+            fn expansion_matches_inserting(image in image(), factor in 2..6usize) {
+                let text: String = image
+                    .iter()
+                    .map(|row| row.iter().map(|g| if *g { '#' } else { '.' }).collect::<String>() + "\n")
+                    .collect();
+                let parsed = Image::parse(&text).unwrap();
+                prop_assert_eq!(expanded_distance(&parsed, factor), Ok(distances_by_inserting(&image, factor)));
+            }
+        }
+    }
 }
@@ -0,0 +1,144 @@
+use std::error::Error;
+use std::fmt::{self, Display};
+
+/// An error found while parsing a day's puzzle input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub day: u8,
+    /// Line number of the offending line, starting from 1
+    pub line: usize,
+    /// Column of the offending character within the line, starting from 1
+    pub column: usize,
+    /// The rule that the input broke, such as what the parser expected to find
+    pub message: String,
+    /// The offending line itself, so that the error can be shown in context
+    pub source_line: String,
+}
+
+impl ParseError {
+    /// Creates an error pointing at the character in `column` of `line`
+    pub fn new(day: u8, line_number: usize, line: &str, column: usize, message: impl Into<String>) -> Self {
+        ParseError {
+            day,
+            line: line_number,
+            column,
+            message: message.into(),
+            source_line: line.to_string(),
+        }
+    }
+
+    /// Creates an error pointing at `token`, which must be a slice of `line`
+    pub(crate) fn at(day: u8, line_number: usize, line: &str, token: &str, message: impl Into<String>) -> Self {
+        let offset = token.as_ptr() as usize - line.as_ptr() as usize;
+        debug_assert!(offset <= line.len(), "Token is not part of the line.");
+        ParseError::new(day, line_number, line, line[..offset].chars().count() + 1, message)
+    }
+
+    /// Creates an error pointing just past the end of `line`, for when something is missing
+    pub(crate) fn after(day: u8, line_number: usize, line: &str, message: impl Into<String>) -> Self {
+        ParseError::at(day, line_number, line, &line[line.len()..], message)
+    }
+}
+
+impl Display for ParseError {
+    /// Shows the error like a compiler diagnostic, with a caret under the offending column:
+    ///
+    /// ```text
+    /// day 04, line 2, column 30: expected ` | ` separator in card line
+    ///   |
+    /// 2 | Card 2: 13 32 20 16 61  61 30
+    ///   |                              ^
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "day {:02}, line {}, column {}: {}",
+            self.day, self.line, self.column, self.message
+        )?;
+
+        let gutter = " ".repeat(self.line.to_string().len());
+        writeln!(f, "{gutter} |")?;
+        writeln!(f, "{} | {}", self.line, self.source_line)?;
+        write!(f, "{gutter} | {}^", " ".repeat(self.column - 1))
+    }
+}
+
+impl Error for ParseError {}
+
+/// Why a part can't be solved from input that parsed, such as day 8's network having no `AAA` to start from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolveError {
+    pub day: u8,
+    /// What the part needed but didn't find, or what went wrong
+    pub message: String,
+}
+
+impl SolveError {
+    pub fn new(day: u8, message: impl Into<String>) -> Self {
+        SolveError {
+            day,
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "day {:02}: {}", self.day, self.message)
+    }
+}
+
+impl Error for SolveError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day04::Scratchcards;
+    use crate::day05::Almanac;
+    use crate::Solution;
+
+    #[test]
+    fn caret_under_offending_token() {
+        let line = "seed-to-soyl map:";
+        let error = ParseError::at(5, 3, line, &line[8..12], "unknown map category `soyl`");
+        assert_eq!(error.column, 9);
+        assert_eq!(
+            error.to_string(),
+            "day 05, line 3, column 9: unknown map category `soyl`\n  |\n3 | seed-to-soyl map:\n  |         ^"
+        );
+    }
+
+    #[test]
+    fn caret_after_truncated_line() {
+        let line = "Card 2: 13 32";
+        let error = ParseError::after(4, 12, line, "expected ` | ` separator in card line");
+        assert_eq!(error.column, 14);
+        let caret = format!("   | {}^", " ".repeat(13));
+        assert!(error.to_string().ends_with(&format!("\n12 | Card 2: 13 32\n{caret}")));
+    }
+
+    #[test]
+    fn columns_count_characters_not_bytes() {
+        let line = "Café: 3 × 4";
+        let error = ParseError::at(1, 1, line, &line[line.find('×').unwrap()..], "expected a number");
+        assert_eq!(error.column, 9);
+        assert!(error.to_string().ends_with("\n  |         ^"));
+        assert_eq!(ParseError::after(1, 1, line, "expected more").column, 12);
+    }
+
+    #[test]
+    fn diagnostics_from_day_parsers() {
+        let error = Scratchcards::parse("Card 1: 41 48 83 86 17").err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "day 04, line 1, column 23: expected ` | ` separator in card line\n  |\n1 | Card 1: 41 48 83 86 17\n  |                       ^"
+        );
+
+        let almanac = "seeds: 79 14\n\nseed-to-soyl map:\n50 98 2\n";
+        let error = Almanac::parse(almanac).err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "day 05, line 3, column 9: unknown map category `soyl`\n  |\n3 | seed-to-soyl map:\n  |         ^"
+        );
+    }
+}
@@ -0,0 +1,314 @@
+use std::borrow::Borrow;
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{Display, Write};
+use std::hash::Hash;
+use std::ops::Index;
+
+/// The index of a node in a [`Graph`], handed out when the node is interned
+pub type NodeId = usize;
+
+/// A graph whose nodes are interned values, such as names or coordinates, with a weight or label on each edge
+///
+/// Nodes are stored once and referred to by [`NodeId`] afterwards, so traversals work on plain indices instead of
+/// hashing the node values at every step.
+#[derive(Debug, Clone)]
+pub struct Graph<N, E = ()> {
+    directed: bool,
+    nodes: Vec<N>,
+    ids: HashMap<N, NodeId>,
+    edges: Vec<Vec<(NodeId, E)>>,
+}
+
+impl<N: Hash + Eq + Clone, E> Graph<N, E> {
+    /// Creates a graph where each edge goes one way only
+    pub fn directed() -> Self {
+        Graph {
+            directed: true,
+            nodes: Vec::new(),
+            ids: HashMap::new(),
+            edges: Vec::new(),
+        }
+    }
+
+    /// Creates a graph where each edge can be followed both ways
+    pub fn undirected() -> Self {
+        Graph {
+            directed: false,
+            ..Self::directed()
+        }
+    }
+
+    /// Returns the ID of the node, adding it to the graph if it's new
+    pub fn intern(&mut self, node: N) -> NodeId {
+        if let Some(&id) = self.ids.get(&node) {
+            return id;
+        }
+        let id = self.nodes.len();
+        self.nodes.push(node.clone());
+        self.ids.insert(node, id);
+        self.edges.push(Vec::new());
+        id
+    }
+
+    /// Returns the ID of the node, if it's in the graph
+    pub fn id<Q>(&self, node: &Q) -> Option<NodeId>
+    where
+        N: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.ids.get(node).copied()
+    }
+
+    /// Adds an edge between two nodes, interning them if they're new
+    pub fn add_edge(&mut self, from: N, to: N, edge: E)
+    where
+        E: Clone,
+    {
+        let (from, to) = (self.intern(from), self.intern(to));
+        if !self.directed {
+            self.edges[to].push((from, edge.clone()));
+        }
+        self.edges[from].push((to, edge));
+    }
+}
+
+impl<N, E> Graph<N, E> {
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn is_directed(&self) -> bool {
+        self.directed
+    }
+
+    /// Every node along with its ID, in the order they were interned
+    pub fn nodes(&self) -> impl Iterator<Item = (NodeId, &N)> {
+        self.nodes.iter().enumerate()
+    }
+
+    /// The edges leaving a node, in the order they were added
+    pub fn edges(&self, id: NodeId) -> &[(NodeId, E)] {
+        &self.edges[id]
+    }
+
+    /// The nodes reachable from a node by following one edge
+    pub fn neighbours(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        self.edges[id].iter().map(|(to, _)| *to)
+    }
+
+    /// Every node reachable from `start`, in breadth-first order
+    pub fn bfs(&self, start: NodeId) -> Vec<NodeId> {
+        let mut order = Vec::new();
+        self.breadth_first(start, |id, _| order.push(id));
+        order
+    }
+
+    /// The number of edges on the shortest path from `start` to each node, indexed by [`NodeId`]
+    ///
+    /// Nodes that can't be reached from `start` have no distance.
+    pub fn distances(&self, start: NodeId) -> Vec<Option<usize>> {
+        let mut distances = vec![None; self.len()];
+        self.breadth_first(start, |id, distance| distances[id] = Some(distance));
+        distances
+    }
+
+    fn breadth_first(&self, start: NodeId, mut visit: impl FnMut(NodeId, usize)) {
+        let mut seen = vec![false; self.len()];
+        let mut queue = VecDeque::from([(start, 0)]);
+        seen[start] = true;
+
+        while let Some((id, distance)) = queue.pop_front() {
+            visit(id, distance);
+            for next in self.neighbours(id) {
+                if !seen[next] {
+                    seen[next] = true;
+                    queue.push_back((next, distance + 1));
+                }
+            }
+        }
+    }
+
+    /// Every node reachable from `start`, in depth-first order
+    pub fn dfs(&self, start: NodeId) -> Vec<NodeId> {
+        let mut order = Vec::new();
+        let mut seen = vec![false; self.len()];
+        let mut stack = vec![start];
+
+        while let Some(id) = stack.pop() {
+            if seen[id] {
+                continue;
+            }
+            seen[id] = true;
+            order.push(id);
+            // Pushed in reverse so that the first edge is followed first
+            stack.extend(
+                self.neighbours(id)
+                    .filter(|n| !seen[*n])
+                    .collect::<Vec<_>>()
+                    .into_iter()
+                    .rev(),
+            );
+        }
+        order
+    }
+
+    /// Finds a cycle reachable from `start`, returning its nodes in the order they are visited
+    ///
+    /// In an undirected graph, going straight back along the edge just taken doesn't count as a cycle. The search
+    /// keeps its own stack rather than recursing, since puzzle graphs can have paths tens of thousands of nodes long.
+    pub fn find_cycle(&self, start: NodeId) -> Option<Vec<NodeId>> {
+        #[derive(Clone, Copy, PartialEq)]
+        enum State {
+            Unseen,
+            OnPath,
+            Done,
+        }
+
+        let mut state = vec![State::Unseen; self.len()];
+        let mut parent = vec![None; self.len()];
+        let mut path = vec![(start, 0)];
+        state[start] = State::OnPath;
+
+        while let Some((id, next_edge)) = path.last_mut() {
+            let id = *id;
+            let Some(&(next, _)) = self.edges[id].get(*next_edge) else {
+                state[id] = State::Done;
+                path.pop();
+                continue;
+            };
+            *next_edge += 1;
+
+            if !self.directed && parent[id] == Some(next) {
+                continue;
+            }
+            match state[next] {
+                State::Unseen => {
+                    state[next] = State::OnPath;
+                    parent[next] = Some(id);
+                    path.push((next, 0));
+                }
+                State::OnPath => {
+                    let from = path.iter().position(|(n, _)| *n == next).unwrap();
+                    return Some(path[from..].iter().map(|(n, _)| *n).collect());
+                }
+                State::Done => {}
+            }
+        }
+        None
+    }
+
+    /// Draws the graph in Graphviz's DOT language, labelling each edge that `label` gives a label for
+    ///
+    /// An undirected graph has each of its edges drawn once.
+    pub fn to_dot(&self, label: impl Fn(&E) -> Option<String>) -> String
+    where
+        N: Display,
+    {
+        let (kind, arrow) = if self.directed {
+            ("digraph", "->")
+        } else {
+            ("graph", "--")
+        };
+        let mut dot = format!("{kind} {{\n");
+        for (id, node) in self.nodes.iter().enumerate() {
+            writeln!(dot, "    {id} [label=\"{}\"];", escape(&node.to_string())).unwrap();
+        }
+        for (from, edges) in self.edges.iter().enumerate() {
+            for (to, edge) in edges {
+                if !self.directed && *to < from {
+                    continue;
+                }
+                write!(dot, "    {from} {arrow} {to}").unwrap();
+                if let Some(label) = label(edge) {
+                    write!(dot, " [label=\"{}\"]", escape(&label)).unwrap();
+                }
+                dot.push_str(";\n");
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+impl<N, E> Index<NodeId> for Graph<N, E> {
+    type Output = N;
+
+    fn index(&self, id: NodeId) -> &N {
+        &self.nodes[id]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// AAA -> BBB -> CCC -> AAA, with DDD hanging off BBB
+    fn triangle() -> Graph<&'static str, char> {
+        let mut graph = Graph::directed();
+        graph.add_edge("AAA", "BBB", 'L');
+        graph.add_edge("BBB", "CCC", 'L');
+        graph.add_edge("BBB", "DDD", 'R');
+        graph.add_edge("CCC", "AAA", 'L');
+        graph
+    }
+
+    #[test]
+    fn interns_each_node_once() {
+        let mut graph = triangle();
+        assert_eq!(graph.len(), 4);
+        assert_eq!(graph.intern("CCC"), 2);
+        assert_eq!(graph.id("DDD"), Some(3));
+        assert_eq!(graph.id("ZZZ"), None);
+        assert_eq!(graph[1], "BBB");
+        assert_eq!(graph.edges(1), [(2, 'L'), (3, 'R')]);
+    }
+
+    #[test]
+    fn traverses_breadth_and_depth_first() {
+        let mut graph = Graph::directed();
+        for (from, to) in [(0, 1), (0, 2), (1, 3), (2, 4), (3, 5)] {
+            graph.add_edge(from, to, ());
+        }
+        assert_eq!(graph.bfs(0), [0, 1, 2, 3, 4, 5]);
+        assert_eq!(graph.dfs(0), [0, 1, 3, 5, 2, 4]);
+        assert_eq!(graph.distances(1), [None, Some(0), None, Some(1), None, Some(2)]);
+    }
+
+    #[test]
+    fn finds_directed_cycles() {
+        let graph = triangle();
+        assert_eq!(graph.find_cycle(0), Some(vec![0, 1, 2]));
+        assert_eq!(graph.find_cycle(3), None);
+    }
+
+    #[test]
+    fn back_edges_are_not_undirected_cycles() {
+        let mut graph = Graph::undirected();
+        graph.add_edge('a', 'b', ());
+        graph.add_edge('b', 'c', ());
+        assert_eq!(graph.find_cycle(0), None);
+        assert_eq!(graph.distances(2), [Some(2), Some(1), Some(0)]);
+
+        graph.add_edge('c', 'a', ());
+        assert_eq!(graph.find_cycle(1).map(|c| c.len()), Some(3));
+    }
+
+    #[test]
+    fn exports_dot() {
+        let mut graph = Graph::undirected();
+        graph.add_edge("a", "b\"", 1);
+        assert_eq!(
+            graph.to_dot(|w| Some(w.to_string())),
+            "graph {\n    0 [label=\"a\"];\n    1 [label=\"b\\\"\"];\n    0 -- 1 [label=\"1\"];\n}\n"
+        );
+        assert!(triangle().to_dot(|_| None).contains("    2 -> 0;\n"));
+    }
+}
@@ -0,0 +1,274 @@
+use crate::ParseError;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::{self, Display};
+use std::ops::{Index, IndexMut};
+
+/// A position in a grid, as (row, column)
+pub type Coords = (usize, usize);
+
+/// One of the four directions between orthogonally adjacent cells
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    North,
+    South,
+    East,
+    West,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [Direction::North, Direction::South, Direction::East, Direction::West];
+
+    pub fn opposite(&self) -> Self {
+        match self {
+            Self::North => Self::South,
+            Self::South => Self::North,
+            Self::East => Self::West,
+            Self::West => Self::East,
+        }
+    }
+
+    /// The change in (row, column) when moving one cell in this direction
+    pub fn vector(&self) -> (isize, isize) {
+        match self {
+            Self::North => (-1, 0),
+            Self::South => (1, 0),
+            Self::East => (0, 1),
+            Self::West => (0, -1),
+        }
+    }
+}
+
+/// Changes in (row, column) to reach each of the eight cells surrounding a cell
+const SURROUNDING: [(isize, isize); 8] = [(-1, -1), (-1, 0), (-1, 1), (0, -1), (0, 1), (1, -1), (1, 0), (1, 1)];
+
+/// A rectangular grid of cells, such as a map drawn in the puzzle input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// Parses a grid drawn as text, one row per line, mapping each character to a cell
+    ///
+    /// The mapping is given the character, its coordinates and the line it's on, so that it can report exactly where
+    /// a bad character is. Every line must be as wide as the first.
+    pub fn parse(
+        day: u8,
+        text: &str,
+        mut cell: impl FnMut(char, Coords, &str) -> Result<T, ParseError>,
+    ) -> Result<Self, ParseError> {
+        let width = text.lines().next().unwrap_or_default().chars().count();
+        let mut cells = Vec::with_capacity(text.len());
+        let mut height = 0;
+
+        for (row, line) in text.lines().enumerate() {
+            let mut col = 0;
+            for c in line.chars() {
+                if col == width {
+                    let token = line.char_indices().nth(width).map_or("", |(i, _)| &line[i..]);
+                    return Err(ParseError::at(day, row + 1, line, token, row_width(width)));
+                }
+                cells.push(cell(c, (row, col), line)?);
+                col += 1;
+            }
+            if col < width {
+                return Err(ParseError::after(day, row + 1, line, row_width(width)));
+            }
+            height += 1;
+        }
+
+        Ok(Grid { width, height, cells })
+    }
+
+    /// Creates a grid of the given size, filling each cell from its coordinates
+    pub fn from_fn(width: usize, height: usize, mut cell: impl FnMut(Coords) -> T) -> Self {
+        let cells = (0..height)
+            .flat_map(|row| (0..width).map(move |col| (row, col)))
+            .map(&mut cell)
+            .collect();
+        Grid { width, height, cells }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Whether the coordinates are inside the grid
+    pub fn contains(&self, (row, col): Coords) -> bool {
+        row < self.height && col < self.width
+    }
+
+    pub fn get(&self, coords: Coords) -> Option<&T> {
+        self.contains(coords)
+            .then(|| &self.cells[coords.0 * self.width + coords.1])
+    }
+
+    pub fn get_mut(&mut self, coords: Coords) -> Option<&mut T> {
+        self.contains(coords)
+            .then(|| &mut self.cells[coords.0 * self.width + coords.1])
+    }
+
+    /// Moves from a cell by the given (row, column) change, if that stays inside the grid
+    pub fn offset(&self, (row, col): Coords, (d_row, d_col): (isize, isize)) -> Option<Coords> {
+        let moved = (row.checked_add_signed(d_row)?, col.checked_add_signed(d_col)?);
+        self.contains(moved).then_some(moved)
+    }
+
+    /// Moves one cell from a cell in the given direction, if that stays inside the grid
+    pub fn step(&self, coords: Coords, direction: Direction) -> Option<Coords> {
+        self.offset(coords, direction.vector())
+    }
+
+    /// The orthogonally adjacent cells inside the grid, with the direction to each
+    pub fn neighbours4(&self, coords: Coords) -> impl Iterator<Item = (Direction, Coords)> + '_ {
+        Direction::ALL
+            .into_iter()
+            .filter_map(move |d| Some((d, self.step(coords, d)?)))
+    }
+
+    /// The up to eight cells surrounding a cell, including diagonals
+    pub fn neighbours8(&self, coords: Coords) -> impl Iterator<Item = Coords> + '_ {
+        SURROUNDING.into_iter().filter_map(move |v| self.offset(coords, v))
+    }
+
+    /// Every cell along with its coordinates, row by row
+    pub fn iter(&self) -> impl Iterator<Item = (Coords, &T)> {
+        self.cells
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| ((i / self.width, i % self.width), cell))
+    }
+
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.cells[row * self.width..(row + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(|row| self.row(row))
+    }
+
+    pub fn column(&self, col: usize) -> impl Iterator<Item = &T> {
+        self.cells.iter().skip(col).step_by(self.width.max(1)).take(self.height)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(|col| self.column(col))
+    }
+}
+
+fn row_width(width: usize) -> String {
+    format!("expected every row to be {width} characters wide, like the first")
+}
+
+impl<T> Index<Coords> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, coords: Coords) -> &T {
+        self.get(coords).expect("Coordinates outside of grid.")
+    }
+}
+
+impl<T> IndexMut<Coords> for Grid<T> {
+    fn index_mut(&mut self, coords: Coords) -> &mut T {
+        self.get_mut(coords).expect("Coordinates outside of grid.")
+    }
+}
+
+/// Draws the grid back as text, one row per line
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes the grid as JSON rows of cells
+impl<T: Serialize> Serialize for Grid<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.rows())
+    }
+}
+
+/// Reads a grid back from rows of cells, which must all be as wide as the first
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Grid<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let rows = Vec::<Vec<T>>::deserialize(deserializer)?;
+        let width = rows.first().map_or(0, Vec::len);
+        if let Some((i, row)) = rows.iter().enumerate().find(|(_, row)| row.len() != width) {
+            return Err(D::Error::custom(format!(
+                "row {} is {} cells wide, but row 1 is {width}",
+                i + 1,
+                row.len()
+            )));
+        }
+        Ok(Grid {
+            width,
+            height: rows.len(),
+            cells: rows.into_iter().flatten().collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chars(text: &str) -> Grid<char> {
+        Grid::parse(0, text, |c, _, _| Ok(c)).unwrap()
+    }
+
+    #[test]
+    fn round_trips_through_text() {
+        let text = "467..\n...*.\n..35.\n";
+        let grid = chars(text);
+        assert_eq!((grid.width(), grid.height()), (5, 3));
+        assert_eq!(grid[(1, 3)], '*');
+        assert_eq!(grid.to_string(), text);
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        let grid = Grid::from_fn(2, 3, |(row, col)| (row * 2 + col + 1) as u32);
+        let json = serde_json::to_string(&grid).unwrap();
+        assert_eq!(json, "[[1,2],[3,4],[5,6]]");
+        assert_eq!(serde_json::from_str::<Grid<u32>>(&json).unwrap(), grid);
+        assert!(serde_json::from_str::<Grid<u32>>("[[1,2],[3]]").is_err());
+    }
+
+    #[test]
+    fn rejects_ragged_rows() {
+        let short = Grid::parse(0, "...\n..\n", |c, _, _| Ok(c)).unwrap_err();
+        assert_eq!((short.line, short.column), (2, 3));
+        let long = Grid::parse(0, "...\n....\n", |c, _, _| Ok(c)).unwrap_err();
+        assert_eq!((long.line, long.column), (2, 4));
+    }
+
+    #[test]
+    fn neighbours_stay_in_bounds() {
+        let grid = chars("abc\ndef\n");
+        let corner: Vec<_> = grid.neighbours4((0, 0)).collect();
+        assert_eq!(corner, [(Direction::South, (1, 0)), (Direction::East, (0, 1))]);
+        assert_eq!(grid.neighbours8((0, 0)).count(), 3);
+        assert_eq!(grid.neighbours8((1, 1)).count(), 5);
+    }
+
+    #[test]
+    fn rows_and_columns() {
+        let grid = chars("abc\ndef\n");
+        assert_eq!(grid.row(1), ['d', 'e', 'f']);
+        let columns: Vec<String> = grid.columns().map(|c| c.collect()).collect();
+        assert_eq!(columns, ["ad", "be", "cf"]);
+    }
+}
@@ -0,0 +1,106 @@
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+
+/// The path that stands for standard input, following the usual command line convention
+pub const STDIN: &str = "-";
+
+const BOM: char = '\u{feff}';
+
+/// Reads puzzle input from a file, or from standard input if the path is `-`
+pub fn read(path: &Path) -> io::Result<String> {
+    if path == Path::new(STDIN) {
+        let mut contents = String::new();
+        io::stdin().read_to_string(&mut contents)?;
+        Ok(contents)
+    } else {
+        fs::read_to_string(path)
+    }
+}
+
+/// Reads puzzle input like [`read`], normalizing it for the parsers
+pub fn load(path: &Path) -> io::Result<String> {
+    read(path).map(|raw| normalize(&raw))
+}
+
+/// Puts puzzle input into the shape every parser expects, whatever editor or download tool it went through
+///
+/// The byte order mark is stripped, CRLF line endings become LF, trailing whitespace is removed from each line and
+/// trailing blank lines are dropped. The result ends with exactly one newline, unless it is empty.
+pub fn normalize(raw: &str) -> String {
+    let raw = raw.strip_prefix(BOM).unwrap_or(raw);
+
+    let mut text = String::with_capacity(raw.len());
+    for line in raw.lines() {
+        text.push_str(line.trim_end());
+        text.push('\n');
+    }
+
+    let len = text.trim_end().len();
+    text.truncate(len);
+    if !text.is_empty() {
+        text.push('\n');
+    }
+    text
+}
+
+/// Returns a warning for every line whose width differs from the first line's
+///
+/// Days whose input is a grid work out coordinates from the width of the first line, so a ragged line would silently
+/// shift every coordinate after it.
+pub fn ragged_lines(text: &str) -> Vec<String> {
+    let mut lines = text.lines();
+    let Some(width) = lines.next().map(|l| l.chars().count()) else {
+        return vec![];
+    };
+
+    lines
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let len = line.chars().count();
+            (len != width).then(|| format!("line {} is {len} characters wide, but line 1 is {width}", i + 2))
+        })
+        .collect()
+}
+
+/// Returns a name for the input's source, for showing in messages
+pub fn display_name(path: &Path) -> String {
+    if path == Path::new(STDIN) {
+        "<stdin>".to_string()
+    } else {
+        path.display().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_windows_download() {
+        assert_eq!(
+            normalize("\u{feff}467..114..\r\n...*......\r\n\r\n"),
+            "467..114..\n...*......\n"
+        );
+    }
+
+    #[test]
+    fn adds_missing_final_newline() {
+        assert_eq!(normalize("LR\n\nAAA = (BBB, CCC)"), "LR\n\nAAA = (BBB, CCC)\n");
+    }
+
+    #[test]
+    fn strips_trailing_whitespace() {
+        assert_eq!(normalize("Time:  7  15 \t\n  \n\n"), "Time:  7  15\n");
+        assert_eq!(normalize("\n \n"), "");
+    }
+
+    #[test]
+    fn finds_ragged_lines() {
+        assert!(ragged_lines(".....\n.S-7.\n").is_empty());
+        assert_eq!(
+            ragged_lines(".....\n.S-7\n.|.|.\n"),
+            ["line 2 is 4 characters wide, but line 1 is 5"]
+        );
+    }
+}
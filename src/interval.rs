@@ -0,0 +1,271 @@
+use std::ops::Range;
+
+/// An integer type that can bound an interval, such as `u64` or `i64`
+///
+/// Shifting goes through `i128`, so a set of unsigned values can be shifted down as well as up.
+pub trait Bound: Copy + Ord + Into<i128> + TryFrom<i128> {
+    /// Adds `by` to the value, unless the result doesn't fit in the type
+    fn shift(self, by: i128) -> Option<Self> {
+        self.into().checked_add(by)?.try_into().ok()
+    }
+}
+
+impl<T: Copy + Ord + Into<i128> + TryFrom<i128>> Bound for T {}
+
+/// A set of integers stored as half-open ranges, for working on huge spans of values at once
+///
+/// The ranges are kept sorted, non-empty and apart from each other: overlapping or touching ranges are merged as they
+/// are added, so two sets holding the same values always compare equal.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IntervalSet<T> {
+    ranges: Vec<Range<T>>,
+}
+
+impl<T: Bound> IntervalSet<T> {
+    pub fn new() -> Self {
+        IntervalSet { ranges: Vec::new() }
+    }
+
+    /// The ranges making up the set, in ascending order
+    pub fn ranges(&self) -> &[Range<T>] {
+        &self.ranges
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// The number of values in the set
+    pub fn len(&self) -> u128 {
+        self.ranges
+            .iter()
+            .map(|r| (r.end.into() - r.start.into()) as u128)
+            .sum()
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        // The last range starting at or before the value is the only one that could hold it
+        let i = self.ranges.partition_point(|r| r.start <= value);
+        i > 0 && value < self.ranges[i - 1].end
+    }
+
+    /// The smallest value in the set
+    pub fn min(&self) -> Option<T> {
+        self.ranges.first().map(|r| r.start)
+    }
+
+    /// The largest value in the set
+    pub fn max(&self) -> Option<T> {
+        self.ranges
+            .last()
+            .map(|r| r.end.shift(-1).expect("Ranges are never empty."))
+    }
+
+    /// Adds every value in the range to the set
+    pub fn insert(&mut self, range: Range<T>) {
+        if range.is_empty() {
+            return;
+        }
+        // Ranges ending before this one starts are untouched, as are those starting after it ends
+        let first = self.ranges.partition_point(|r| r.end < range.start);
+        let last = self.ranges.partition_point(|r| r.start <= range.end);
+        let merged = match self.ranges[first..last] {
+            [] => range,
+            ref touching => touching[0].start.min(range.start)..touching[touching.len() - 1].end.max(range.end),
+        };
+        self.ranges.splice(first..last, [merged]);
+    }
+
+    /// The values in either set
+    pub fn union(&self, other: &Self) -> Self {
+        let mut union = self.clone();
+        for range in &other.ranges {
+            union.insert(range.clone());
+        }
+        union
+    }
+
+    /// The values in both sets
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut ranges = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while let (Some(a), Some(b)) = (self.ranges.get(i), other.ranges.get(j)) {
+            let overlap = a.start.max(b.start)..a.end.min(b.end);
+            if !overlap.is_empty() {
+                ranges.push(overlap);
+            }
+            // Whichever range ends first can't overlap anything further along the other set
+            if a.end <= b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        IntervalSet { ranges }
+    }
+
+    /// The values in this set but not the other
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut ranges = Vec::new();
+        let mut j = 0;
+        for range in &self.ranges {
+            let mut start = range.start;
+            // Skip the ranges that end before this one starts, which can't overlap any later range either
+            while other.ranges.get(j).is_some_and(|r| r.end <= start) {
+                j += 1;
+            }
+            let mut k = j;
+            while let Some(cut) = other.ranges.get(k).filter(|r| r.start < range.end) {
+                if start < cut.start {
+                    ranges.push(start..cut.start);
+                }
+                start = start.max(cut.end);
+                k += 1;
+            }
+            if start < range.end {
+                ranges.push(start..range.end);
+            }
+        }
+        IntervalSet { ranges }
+    }
+
+    /// Splits the set into the values below `at` and the values from `at` upwards
+    pub fn split_at(&self, at: T) -> (Self, Self) {
+        let mut below = Vec::new();
+        let mut above = Vec::new();
+        for range in &self.ranges {
+            if range.end <= at {
+                below.push(range.clone());
+            } else if range.start >= at {
+                above.push(range.clone());
+            } else {
+                below.push(range.start..at);
+                above.push(at..range.end);
+            }
+        }
+        (IntervalSet { ranges: below }, IntervalSet { ranges: above })
+    }
+
+    /// Adds `by` to every value in the set, unless a value would no longer fit in the type
+    pub fn shift(&self, by: i128) -> Option<Self> {
+        let ranges = self
+            .ranges
+            .iter()
+            .map(|r| Some(r.start.shift(by)?..r.end.shift(by)?))
+            .collect::<Option<_>>()?;
+        Some(IntervalSet { ranges })
+    }
+}
+
+impl<T: Bound> Default for IntervalSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Bound> From<Range<T>> for IntervalSet<T> {
+    fn from(range: Range<T>) -> Self {
+        IntervalSet::from_iter([range])
+    }
+}
+
+impl<T: Bound> FromIterator<Range<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = Range<T>>>(iter: I) -> Self {
+        let mut set = IntervalSet::new();
+        for range in iter {
+            set.insert(range);
+        }
+        set
+    }
+}
+
+#[cfg(test)]
+// Single ranges and empty ranges are exactly what these tests are about
+#[allow(clippy::single_range_in_vec_init, clippy::reversed_empty_ranges)]
+mod tests {
+    use super::*;
+
+    fn set<const N: usize>(ranges: [Range<i64>; N]) -> IntervalSet<i64> {
+        IntervalSet::from_iter(ranges)
+    }
+
+    /// Checks an operation against the same operation done one value at a time over `-20..20`
+    fn check(result: &IntervalSet<i64>, expected: impl Fn(i64) -> bool) {
+        let values: Vec<_> = (-20..20).filter(|v| result.contains(*v)).collect();
+        let expected: Vec<_> = (-20..20).filter(|v| expected(*v)).collect();
+        assert_eq!(values, expected);
+        assert_eq!(result.len(), expected.len() as u128);
+        // The ranges must stay sorted and apart
+        for pair in result.ranges().windows(2) {
+            assert!(pair[0].end < pair[1].start, "{result:?} has touching ranges");
+        }
+    }
+
+    #[test]
+    fn merges_overlapping_and_touching_ranges() {
+        assert_eq!(set([5..8, 1..3, 3..4, 7..10]).ranges(), [1..4, 5..10]);
+        assert_eq!(set([1..3, 5..7, 9..11, 2..10]).ranges(), [1..11]);
+        assert_eq!(set([4..4, 2..1]), IntervalSet::new());
+        assert_eq!(set([0..2, 10..12, 5..6]).ranges(), [0..2, 5..6, 10..12]);
+    }
+
+    #[test]
+    fn contains_and_bounds() {
+        let s = set([-5..-2, 3..6]);
+        assert!(s.contains(-5) && s.contains(5));
+        assert!(!s.contains(-2) && !s.contains(0) && !s.contains(6));
+        assert_eq!((s.min(), s.max()), (Some(-5), Some(5)));
+        assert_eq!(IntervalSet::<u64>::new().min(), None);
+    }
+
+    #[test]
+    fn set_operations_match_pointwise() {
+        let sets = [
+            set([]),
+            set([-20..20]),
+            set([-3..4]),
+            set([-10..-5, 0..2, 6..9]),
+            set([-7..1, 8..15]),
+            set([-20..-19, 19..20]),
+        ];
+        for a in &sets {
+            for b in &sets {
+                check(&a.union(b), |v| a.contains(v) || b.contains(v));
+                check(&a.intersection(b), |v| a.contains(v) && b.contains(v));
+                check(&a.difference(b), |v| a.contains(v) && !b.contains(v));
+            }
+        }
+    }
+
+    #[test]
+    fn difference_splits_ranges() {
+        let s = set([0..10]).difference(&set([2..3, 5..7]));
+        assert_eq!(s.ranges(), [0..2, 3..5, 7..10]);
+        assert_eq!(set([0..10]).difference(&set([0..10])), IntervalSet::new());
+    }
+
+    #[test]
+    fn splits_at_a_value() {
+        let (below, above) = set([0..5, 8..12]).split_at(10);
+        assert_eq!(
+            (below.ranges(), above.ranges()),
+            ([0..5, 8..10].as_slice(), [10..12].as_slice())
+        );
+        let (below, above) = set([0..5]).split_at(5);
+        assert_eq!((below.ranges(), above), ([0..5].as_slice(), IntervalSet::new()));
+        let (below, above) = set([0..5]).split_at(-1);
+        assert_eq!((below, above.ranges()), (IntervalSet::new(), [0..5].as_slice()));
+    }
+
+    #[test]
+    fn shifts_within_the_type() {
+        let s: IntervalSet<u64> = IntervalSet::from_iter([50..98, 98..100]);
+        assert_eq!(s.shift(-48).unwrap().ranges(), [2..52]);
+        assert_eq!(s.shift(-51), None);
+        assert_eq!(IntervalSet::from(0..u64::MAX).shift(1), None);
+        assert_eq!(
+            set([-5..5]).shift(i64::MAX as i128 - 5).unwrap().max(),
+            Some(i64::MAX - 1)
+        );
+    }
+}
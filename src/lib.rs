@@ -0,0 +1,67 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fmt::Display;
+
+pub use error::{ParseError, SolveError};
+
+pub mod answers;
+#[path = "day01/day01.rs"]
+pub mod day01;
+#[path = "day02/day02.rs"]
+pub mod day02;
+#[path = "day03/day03.rs"]
+pub mod day03;
+#[path = "day04/day04.rs"]
+pub mod day04;
+#[path = "day05/day05.rs"]
+pub mod day05;
+#[path = "day06/day06.rs"]
+pub mod day06;
+#[path = "day07/day07.rs"]
+pub mod day07;
+#[path = "day08/day08.rs"]
+pub mod day08;
+#[path = "day09/day09.rs"]
+pub mod day09;
+#[path = "day10/day10.rs"]
+pub mod day10;
+#[path = "day11/day11.rs"]
+pub mod day11;
+pub mod error;
+pub mod graph;
+pub mod grid;
+pub mod input;
+pub mod interval;
+pub mod math;
+pub mod registry;
+#[cfg(target_arch = "wasm32")]
+pub mod wasm;
+
+/// A day's puzzle, parsed once from the puzzle input and then solved in two parts
+///
+/// The parsed form also has a JSON representation, so that other tools can generate or inspect puzzle data without
+/// knowing the day's text format. Anything a part relies on, such as day 5's maps leading to a location, is checked
+/// when reading it back just as when parsing.
+pub trait Solution: Sized + Serialize + DeserializeOwned {
+    /// The type of both parts' answers
+    type Answer: Display;
+
+    /// Whether the input is a rectangular grid of characters, where lines of different widths are suspicious
+    const GRID: bool = false;
+
+    /// Parses the puzzle input
+    fn parse(input: &str) -> Result<Self, ParseError>;
+
+    /// Finds whatever in the puzzle input parses but is probably a mistake, such as a number repeated on a scratchcard
+    ///
+    /// This is only asked of input which has already parsed, for the runner's strict mode.
+    fn anomalies(_input: &str) -> Vec<ParseError> {
+        Vec::new()
+    }
+
+    /// Solves the first part of the puzzle, or explains why this input has no answer
+    fn part_a(&self) -> Result<Self::Answer, SolveError>;
+
+    /// Solves the second part of the puzzle, or explains why this input has no answer
+    fn part_b(&self) -> Result<Self::Answer, SolveError>;
+}
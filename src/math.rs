@@ -0,0 +1,228 @@
+use std::error::Error;
+use std::fmt::{self, Display};
+
+/// Why a calculation couldn't give an answer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MathError {
+    /// The answer, or a step on the way to it, doesn't fit in the integer type
+    Overflow,
+    /// A modulus of zero was given
+    ZeroModulus,
+}
+
+impl Display for MathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Overflow => write!(f, "arithmetic overflow"),
+            Self::ZeroModulus => write!(f, "modulus of zero"),
+        }
+    }
+}
+
+impl Error for MathError {}
+
+pub fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b)
+    }
+    a
+}
+
+/// The lowest common multiple of two numbers, which is zero if either of them is
+pub fn lcm(a: u64, b: u64) -> Result<u64, MathError> {
+    if a == 0 || b == 0 {
+        return Ok(0);
+    }
+    (a / gcd(a, b)).checked_mul(b).ok_or(MathError::Overflow)
+}
+
+/// The greatest common divisor of all the numbers, which is zero if there are none
+pub fn gcd_all(numbers: impl IntoIterator<Item = u64>) -> u64 {
+    numbers.into_iter().fold(0, gcd)
+}
+
+/// The lowest common multiple of all the numbers, which is one if there are none
+///
+/// This is when several cycles of different lengths all line up again for the first time.
+pub fn lcm_all(numbers: impl IntoIterator<Item = u64>) -> Result<u64, MathError> {
+    numbers.into_iter().try_fold(1, lcm)
+}
+
+/// Extended Euclidean algorithm, returning `(g, x, y)` where `g` is the non-negative gcd of `a` and `b` and
+/// `a * x + b * y = g`
+pub fn extended_gcd(a: i64, b: i64) -> Result<(i64, i64, i64), MathError> {
+    let (g, x, y) = extended_gcd_wide(a.into(), b.into());
+    // Only a gcd of 2^63, from `i64::MIN` and zero or itself, is out of range
+    let narrow = |n: i128| i64::try_from(n).map_err(|_| MathError::Overflow);
+    Ok((narrow(g)?, narrow(x)?, narrow(y)?))
+}
+
+fn extended_gcd_wide(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1, 0);
+    let (mut old_y, mut y) = (0, 1);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_x, x) = (x, old_x - q * x);
+        (old_y, y) = (y, old_y - q * y);
+    }
+    if old_r < 0 {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+/// Chinese Remainder Theorem: finds the numbers that leave each `(remainder, modulus)` pair's remainder
+///
+/// The moduli don't need to be coprime. The numbers found are all those congruent to `x` modulo `m`, returned as
+/// `Some((x, m))` with `x < m`, or `None` if the congruences contradict each other.
+pub fn crt(congruences: impl IntoIterator<Item = (u64, u64)>) -> Result<Option<(u64, u64)>, MathError> {
+    let (mut x, mut m) = (0, 1);
+    for (remainder, modulus) in congruences {
+        if modulus == 0 {
+            return Err(MathError::ZeroModulus);
+        }
+        let remainder = remainder % modulus;
+
+        // Solve x + m * t = remainder (mod modulus) for t, which needs the gcd to divide the difference
+        let g = gcd(m, modulus);
+        let difference = i128::from(remainder) - i128::from(x);
+        if difference % i128::from(g) != 0 {
+            return Ok(None);
+        }
+        let reduced = i128::from(modulus / g);
+        let (_, inverse, _) = extended_gcd_wide(i128::from(m / g) % reduced, reduced);
+        let t = ((difference / i128::from(g)).rem_euclid(reduced) * inverse.rem_euclid(reduced)) % reduced;
+
+        let combined = lcm(m, modulus)?;
+        // t is below modulus / g, so this stays below the lcm, which fits
+        x = (u128::from(x) + u128::from(m) * t as u128) as u64;
+        m = combined;
+    }
+    Ok(Some((x, m)))
+}
+
+/// The square root of `n` rounded down, worked out exactly rather than through floating point
+///
+/// The root of any `u128` fits in a `u64`, so squares of 64 bit numbers can be worked with without overflowing.
+pub fn isqrt(n: u128) -> u64 {
+    n.isqrt() as u64
+}
+
+/// `base` to the power of `exp`, modulo `modulus`
+pub fn modpow(base: u64, mut exp: u64, modulus: u64) -> Result<u64, MathError> {
+    if modulus == 0 {
+        return Err(MathError::ZeroModulus);
+    }
+    let modulus = u128::from(modulus);
+    let mut base = u128::from(base) % modulus;
+    let mut result = 1 % modulus;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result * base % modulus;
+        }
+        base = base * base % modulus;
+        exp >>= 1;
+    }
+    Ok(result as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn folds_gcd_and_lcm() {
+        assert_eq!(gcd_all([12, 18, 30]), 6);
+        assert_eq!(gcd_all([]), 0);
+        assert_eq!(lcm_all([2, 3, 4]), Ok(12));
+        assert_eq!(lcm_all([]), Ok(1));
+        assert_eq!(lcm_all([5, 0]), Ok(0));
+        // The day 8 example's cycles
+        assert_eq!(lcm_all([2, 3]), Ok(6));
+    }
+
+    #[test]
+    fn lcm_reports_overflow() {
+        assert_eq!(lcm(u64::MAX, u64::MAX), Ok(u64::MAX));
+        assert_eq!(lcm(u64::MAX, u64::MAX - 1), Err(MathError::Overflow));
+        assert_eq!(lcm_all([1 << 40, 3 << 30, 5 << 20]), Ok(15 << 40));
+        assert_eq!(lcm_all([1 << 40, 3, 5, 7 << 30, 11 << 10]), Ok(1155 << 40));
+        assert_eq!(
+            lcm_all([1 << 40, 3, 5, 7, 11, 13, 17, 19, 23]),
+            Err(MathError::Overflow)
+        );
+    }
+
+    #[test]
+    fn extended_euclid_gives_bezout_coefficients() {
+        for (a, b) in [(240, 46), (-240, 46), (17, 0), (0, -5), (i64::MAX, i64::MAX - 1)] {
+            let (g, x, y) = extended_gcd(a, b).unwrap();
+            assert_eq!(g as u64, gcd(a.unsigned_abs(), b.unsigned_abs()));
+            assert_eq!(
+                i128::from(a) * i128::from(x) + i128::from(b) * i128::from(y),
+                i128::from(g)
+            );
+        }
+        assert_eq!(extended_gcd(i64::MIN, 0), Err(MathError::Overflow));
+    }
+
+    #[test]
+    fn crt_with_coprime_moduli() {
+        assert_eq!(crt([(2, 3), (3, 5), (2, 7)]), Ok(Some((23, 105))));
+        assert_eq!(crt([]), Ok(Some((0, 1))));
+    }
+
+    #[test]
+    fn crt_with_shared_factors() {
+        assert_eq!(crt([(2, 4), (4, 6)]), Ok(Some((10, 12))));
+        assert_eq!(crt([(1, 4), (2, 6)]), Ok(None));
+        assert_eq!(crt([(7, 10), (17, 20)]), Ok(Some((17, 20))));
+        assert_eq!(crt([(1, 2), (0, 0)]), Err(MathError::ZeroModulus));
+    }
+
+    #[test]
+    fn crt_checks_every_answer_against_brute_force() {
+        for a in 1..12 {
+            for b in 1..12 {
+                for (ra, rb) in [(0, 0), (1, 2), (a - 1, b / 2)] {
+                    let brute = (0..a * b).find(|x| x % a == ra % a && x % b == rb % b);
+                    let expected = brute.map(|x| (x, lcm(a, b).unwrap()));
+                    assert_eq!(
+                        crt([(ra, a), (rb, b)]),
+                        Ok(expected),
+                        "x = {ra} mod {a}, x = {rb} mod {b}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn crt_reports_overflow() {
+        let big_primes = [(1, 4294967291), (2, 4294967279), (3, 4294967231)];
+        assert_eq!(crt(big_primes), Err(MathError::Overflow));
+    }
+
+    #[test]
+    fn isqrt_is_exact() {
+        assert_eq!(isqrt(0), 0);
+        assert_eq!(isqrt(99), 9);
+        assert_eq!(isqrt(100), 10);
+        let big = u128::from(u64::MAX);
+        assert_eq!(isqrt(big * big), u64::MAX);
+        assert_eq!(isqrt(big * big - 1), u64::MAX - 1);
+        // Floating point rounds this up to 2^26 + 1
+        assert_eq!(isqrt((1 << 52) + (1 << 27)), 1 << 26);
+    }
+
+    #[test]
+    fn modpow_stays_in_range() {
+        assert_eq!(modpow(4, 13, 497), Ok(445));
+        assert_eq!(modpow(u64::MAX, u64::MAX, u64::MAX - 1), Ok(1));
+        assert_eq!(modpow(3, 0, 1), Ok(0));
+        assert_eq!(modpow(3, 2, 0), Err(MathError::ZeroModulus));
+    }
+}
@@ -0,0 +1,154 @@
+use crate::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// One of the two parts of a day's puzzle
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Part {
+    A,
+    B,
+}
+
+impl Part {
+    pub const BOTH: [Part; 2] = [Part::A, Part::B];
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Part::A => write!(f, "a"),
+            Part::B => write!(f, "b"),
+        }
+    }
+}
+
+impl FromStr for Part {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "a" | "A" => Ok(Part::A),
+            "b" | "B" => Ok(Part::B),
+            _ => Err(format!("Unknown part `{s}`, expected `a` or `b`.")),
+        }
+    }
+}
+
+/// A solution whose concrete type has been erased, so that every day can be run the same way
+pub trait Solved {
+    fn part_a(&self) -> Result<String, SolveError>;
+    fn part_b(&self) -> Result<String, SolveError>;
+
+    /// The parsed form as JSON, which [`Day::from_json`] reads back
+    fn to_json(&self) -> String;
+
+    /// Solves the given part of the puzzle, or explains why this input has no answer
+    fn solve(&self, part: Part) -> Result<String, SolveError> {
+        match part {
+            Part::A => self.part_a(),
+            Part::B => self.part_b(),
+        }
+    }
+}
+
+impl<S: Solution> Solved for S {
+    fn part_a(&self) -> Result<String, SolveError> {
+        Solution::part_a(self).map(|answer| answer.to_string())
+    }
+
+    fn part_b(&self) -> Result<String, SolveError> {
+        Solution::part_b(self).map(|answer| answer.to_string())
+    }
+
+    fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("Parsed forms always serialize.")
+    }
+}
+
+/// A completed day of the calendar
+pub struct Day {
+    pub number: u8,
+    /// Whether the day's input is a grid, as described by [`Solution::GRID`]
+    pub grid: bool,
+    parse: fn(&str) -> Result<Box<dyn Solved>, ParseError>,
+    anomalies: fn(&str) -> Vec<ParseError>,
+    from_json: fn(&str) -> Result<Box<dyn Solved>, serde_json::Error>,
+}
+
+impl Day {
+    const fn new<S: Solution + 'static>(number: u8) -> Self {
+        Day {
+            number,
+            grid: S::GRID,
+            parse: |input| Ok(Box::new(S::parse(input)?)),
+            anomalies: S::anomalies,
+            from_json: |json| Ok(Box::new(serde_json::from_str::<S>(json)?)),
+        }
+    }
+
+    /// Parses the puzzle input into this day's solution
+    pub fn parse(&self, input: &str) -> Result<Box<dyn Solved>, ParseError> {
+        (self.parse)(input)
+    }
+
+    /// Reads this day's solution back from the JSON written by [`Solved::to_json`]
+    pub fn from_json(&self, json: &str) -> Result<Box<dyn Solved>, serde_json::Error> {
+        (self.from_json)(json)
+    }
+
+    /// Finds whatever in input that has already parsed is probably a mistake, as described by
+    /// [`Solution::anomalies`]
+    pub fn anomalies(&self, input: &str) -> Vec<ParseError> {
+        (self.anomalies)(input)
+    }
+}
+
+/// Every completed day, in calendar order
+pub static DAYS: [Day; 11] = [
+    Day::new::<day01::Document>(1),
+    Day::new::<day02::GameRecord>(2),
+    Day::new::<day03::Schematic>(3),
+    Day::new::<day04::Scratchcards>(4),
+    Day::new::<day05::Almanac>(5),
+    Day::new::<day06::Races>(6),
+    Day::new::<day07::CamelCards>(7),
+    Day::new::<day08::Network>(8),
+    Day::new::<day09::Report>(9),
+    Day::new::<day10::PipeMaze>(10),
+    Day::new::<day11::Image>(11),
+];
+
+/// Looks up a completed day by its number
+pub fn day(number: u8) -> Option<&'static Day> {
+    DAYS.iter().find(|d| d.number == number)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn examples_round_trip_through_json() {
+        for day in &DAYS {
+            let dir = format!("src/day{:02}", day.number);
+            for entry in fs::read_dir(&dir).unwrap() {
+                let path = entry.unwrap().path();
+                if !path.file_name().unwrap().to_string_lossy().starts_with("example") {
+                    continue;
+                }
+                // A newly set up day's example may not be filled in yet
+                let Ok(solution) = day.parse(&fs::read_to_string(&path).unwrap()) else {
+                    continue;
+                };
+                let json = solution.to_json();
+                let read_back = day
+                    .from_json(&json)
+                    .unwrap_or_else(|e| panic!("{}: {e}", path.display()));
+                assert_eq!(read_back.to_json(), json, "{}", path.display());
+            }
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use crate::input;
+use crate::registry::{self, Part, DAYS};
+use wasm_bindgen::prelude::*;
+
+/// Both answers to a day's puzzle, either of which is missing if that part can't be solved from the input
+#[wasm_bindgen(getter_with_clone)]
+pub struct Answers {
+    pub part_a: Option<String>,
+    pub part_b: Option<String>,
+    /// Why each missing answer is missing
+    pub problems: Vec<String>,
+}
+
+/// Returns the number of every completed day, in calendar order
+#[wasm_bindgen]
+pub fn days() -> Vec<u8> {
+    DAYS.iter().map(|day| day.number).collect()
+}
+
+/// Solves both parts of a day from its puzzle input, or explains why the input is malformed
+///
+/// The input is normalized first, as when it's read from a file, so that text pasted from anywhere works.
+#[wasm_bindgen]
+pub fn solve(day: u8, input: &str) -> Result<Answers, JsError> {
+    let day = registry::day(day).ok_or_else(|| JsError::new(&format!("Day {day} has not been completed.")))?;
+    let solution = day
+        .parse(&input::normalize(input))
+        .map_err(|e| JsError::new(&e.to_string()))?;
+    let mut problems = Vec::new();
+    let [part_a, part_b] = Part::BOTH.map(|part| {
+        solution
+            .solve(part)
+            .map_err(|e| problems.push(format!("Could not solve part {part}: {e}")))
+            .ok()
+    });
+    Ok(Answers {
+        part_a,
+        part_b,
+        problems,
+    })
+}
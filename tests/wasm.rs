@@ -0,0 +1,58 @@
+//! The browser bindings, run under a headless WebAssembly runtime with
+//! `cargo test --no-default-features --target wasm32-unknown-unknown --test wasm`, or `make wasm-test`
+
+#![cfg(target_arch = "wasm32")]
+
+use aoc2023::wasm::{days, solve};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+/// Examples with a known answer to both parts, as recorded in `answers.toml`
+const EXAMPLES: [(u8, &str, &str, &str); 8] = [
+    (2, include_str!("../src/day02/example.txt"), "8", "2286"),
+    (3, include_str!("../src/day03/example.txt"), "4361", "467835"),
+    (4, include_str!("../src/day04/example.txt"), "13", "30"),
+    (5, include_str!("../src/day05/example.txt"), "35", "46"),
+    (6, include_str!("../src/day06/example.txt"), "288", "71503"),
+    (7, include_str!("../src/day07/example.txt"), "6440", "5905"),
+    (9, include_str!("../src/day09/example.txt"), "114", "2"),
+    (11, include_str!("../src/day11/example.txt"), "374", "82000210"),
+];
+
+#[wasm_bindgen_test]
+fn solves_examples() {
+    for (day, input, a, b) in EXAMPLES {
+        let answers = solve(day, input).unwrap();
+        assert_eq!(
+            (answers.part_a.as_deref(), answers.part_b.as_deref()),
+            (Some(a), Some(b)),
+            "day {day}"
+        );
+        assert!(answers.problems.is_empty(), "day {day}");
+    }
+}
+
+#[wasm_bindgen_test]
+fn normalizes_pasted_input() {
+    let pasted = EXAMPLES[4].1.replace('\n', "  \r\n") + "\r\n\r\n";
+    assert_eq!(solve(6, &pasted).unwrap().part_b.as_deref(), Some("71503"));
+}
+
+#[wasm_bindgen_test]
+fn lists_every_day() {
+    assert_eq!(days(), (1..=11).collect::<Vec<u8>>());
+}
+
+#[wasm_bindgen_test]
+fn keeps_the_answer_to_the_part_that_can_be_solved() {
+    // The part B example has no `AAA` for part A to start from
+    let answers = solve(8, include_str!("../src/day08/example3.txt")).unwrap();
+    assert_eq!((answers.part_a, answers.part_b.as_deref()), (None, Some("6")));
+    assert_eq!(answers.problems.len(), 1);
+    assert!(answers.problems[0].contains("AAA"));
+}
+
+#[wasm_bindgen_test]
+fn rejects_bad_input() {
+    assert!(solve(4, "Card 1: 41 48 83").is_err());
+    assert!(solve(25, "").is_err());
+}